@@ -1,13 +1,55 @@
 /**
  * @name ask.js
- * @version 0.1.0
+ * @version 0.10.0
  * @url https://github.com/lencx/ChatGPT/tree/main/scripts/ask.js
  */
 
-class ChatAsk {
+// Assigned rather than declared so the script can be re-evaluated in place
+window.ChatAsk = class ChatAsk {
+  // Every ChatGPT selector the app relies on, in order of preference.
+  // Update them here when the page markup changes.
+  static SELECTORS = {
+    composer: ['#prompt-textarea', 'textarea'],
+    sendButton: ['[data-testid="send-button"]', 'main form button'],
+    stopButton: ['[data-testid="stop-button"]', 'button[aria-label="Stop generating"]'],
+    assistantMessage: ['[data-message-author-role="assistant"]'],
+    fileInput: ['input[type="file"][multiple]', 'input[type="file"]'],
+    closeSidebar: ['[data-testid="close-sidebar-button"]', 'button[aria-label="Close sidebar"]'],
+    openSidebar: ['[data-testid="open-sidebar-button"]', 'button[aria-label="Open sidebar"]'],
+    modelSwitcher: ['[data-testid="model-switcher-dropdown-button"]', 'button[aria-label^="Model selector"]'],
+    modelOption: ['[role="menu"] [data-testid^="model-switcher-"]', '[role="menu"] [role="menuitem"]'],
+  };
+
+  static queryAll(selectors) {
+    for (const selector of selectors) {
+      const elements = document.querySelectorAll(selector);
+      if (elements.length) return elements;
+    }
+    return [];
+  }
+
+  // Resolves to the matching elements once there are any, or to none after timeout ms
+  static waitFor(selectors, timeout) {
+    const started = Date.now();
+    return new Promise((resolve) => {
+      const poll = () => {
+        const elements = [...ChatAsk.queryAll(selectors)];
+        if (elements.length || Date.now() - started > timeout) resolve(elements);
+        else setTimeout(poll, 50);
+      };
+      poll();
+    });
+  }
+
+  static composer() {
+    return ChatAsk.queryAll(ChatAsk.SELECTORS.composer)[0];
+  }
+
   static sync(message) {
-    const inputElement = document.querySelector('textarea');
-    if (inputElement) {
+    const inputElement = ChatAsk.composer();
+    if (!inputElement) return;
+
+    if (inputElement instanceof HTMLTextAreaElement) {
       const nativeTextareaSetter = Object.getOwnPropertyDescriptor(window.HTMLTextAreaElement.prototype, 'value').set;
       nativeTextareaSetter.call(inputElement, message);
       const inputEvent = new InputEvent('input', {
@@ -15,11 +57,30 @@ class ChatAsk {
         cancelable: true,
       });
       inputElement.dispatchEvent(inputEvent);
+    } else {
+      // contenteditable composer, go through the editing commands so the page sees it
+      inputElement.focus();
+      document.execCommand('selectAll', false);
+      document.execCommand('insertText', false, message);
+    }
+  }
+
+  // Inserts text at the cursor, replacing the selection, instead of the whole input
+  static insert(text) {
+    const inputElement = ChatAsk.composer();
+    if (!inputElement) return;
+
+    inputElement.focus();
+    if (inputElement instanceof HTMLTextAreaElement) {
+      inputElement.setRangeText(text, inputElement.selectionStart, inputElement.selectionEnd, 'end');
+      inputElement.dispatchEvent(new InputEvent('input', { bubbles: true, cancelable: true }));
+    } else {
+      document.execCommand('insertText', false, text);
     }
   }
 
   static submit() {
-    const btns = document.querySelectorAll('main form button');
+    const btns = ChatAsk.queryAll(ChatAsk.SELECTORS.sendButton);
     const btn = btns[btns.length - 1];
 
     if (btn) {
@@ -28,6 +89,155 @@ class ChatAsk {
       btn.click();
     }
   }
-}
 
-window.ChatAsk = ChatAsk;
\ No newline at end of file
+  static send(message) {
+    ChatAsk.sync(message);
+    // Give the page a tick to pick up the input and enable the send button
+    setTimeout(() => ChatAsk.submit(), 100);
+  }
+
+  // Returns false when nothing is being generated
+  static stop() {
+    const btn = ChatAsk.queryAll(ChatAsk.SELECTORS.stopButton)[0];
+    if (!btn) return false;
+    btn.click();
+    return true;
+  }
+
+  // Runs action for a Tauri style accelerator, e.g. `CmdOrCtrl+Shift+S` or `Escape`.
+  // The action returns false to leave the key to the page. Binding a name again
+  // replaces its previous accelerator, an empty one only unbinds it.
+  static bindShortcut(name, accelerator, action) {
+    const bound = (window.__chatAskShortcuts ??= {});
+    if (bound[name]) window.removeEventListener('keydown', bound[name]);
+    delete bound[name];
+    if (!accelerator) return;
+
+    const parts = accelerator.toLowerCase().split('+');
+    const key = parts.pop();
+    const isMac = navigator.platform.startsWith('Mac');
+    const wants = (...names) => names.some((name) => parts.includes(name));
+    const modifiers = {
+      ctrlKey: wants('ctrl', 'control') || (!isMac && wants('cmdorctrl', 'commandorcontrol')),
+      metaKey: wants('cmd', 'command', 'super') || (isMac && wants('cmdorctrl', 'commandorcontrol')),
+      shiftKey: wants('shift'),
+      altKey: wants('alt', 'option'),
+    };
+
+    bound[name] = (event) => {
+      if (event.defaultPrevented || event.key.toLowerCase() !== key) return;
+      if (Object.entries(modifiers).some(([modifier, on]) => event[modifier] !== on)) return;
+      if (action() !== false) event.preventDefault();
+    };
+    window.addEventListener('keydown', bound[name]);
+  }
+
+  // files: [{ name, type, data }] with base64 data, as sent by the app on drop.
+  // Returns false when the page has no file input to attach them to
+  static attach(files) {
+    const input = ChatAsk.queryAll(ChatAsk.SELECTORS.fileInput)[0];
+    if (!input) return false;
+
+    const transfer = new DataTransfer();
+    for (const { name, type, data } of files) {
+      const bytes = Uint8Array.from(atob(data), (c) => c.charCodeAt(0));
+      transfer.items.add(new File([bytes], name, { type }));
+    }
+    input.files = transfer.files;
+    input.dispatchEvent(new Event('change', { bubbles: true }));
+    return true;
+  }
+
+  // true when open, false when collapsed, null when the page has no sidebar toggle
+  static sidebarOpen() {
+    if (ChatAsk.queryAll(ChatAsk.SELECTORS.closeSidebar).length) return true;
+    if (ChatAsk.queryAll(ChatAsk.SELECTORS.openSidebar).length) return false;
+    return null;
+  }
+
+  // Returns the new state, or null when there is nothing to toggle
+  static setSidebar(open) {
+    const current = ChatAsk.sidebarOpen();
+    if (current === null) return null;
+    if (current !== open) {
+      const selectors = current ? ChatAsk.SELECTORS.closeSidebar : ChatAsk.SELECTORS.openSidebar;
+      ChatAsk.queryAll(selectors)[0].click();
+    }
+    return open;
+  }
+
+  static toggleSidebar() {
+    const current = ChatAsk.sidebarOpen();
+    return current === null ? null : ChatAsk.setSidebar(!current);
+  }
+
+  // The sidebar renders after load, so wait for its toggle for a while
+  static collapseSidebar() {
+    if (ChatAsk.setSidebar(false) !== null) return;
+    const observer = new MutationObserver(() => {
+      if (ChatAsk.setSidebar(false) !== null) observer.disconnect();
+    });
+    observer.observe(document.documentElement, { childList: true, subtree: true });
+    setTimeout(() => observer.disconnect(), 10000);
+  }
+
+  // Mutes every audio and video element, also ones created later such as voice replies.
+  // Unmuting only touches the elements in the page.
+  static setMuted(muted) {
+    window.__chatAskMuted = muted;
+    document.querySelectorAll('audio, video').forEach((el) => {
+      el.muted = muted;
+    });
+
+    const play = HTMLMediaElement.prototype.play;
+    if (!play.chatAskPatched) {
+      HTMLMediaElement.prototype.play = function () {
+        if (window.__chatAskMuted) this.muted = true;
+        return play.apply(this, arguments);
+      };
+      HTMLMediaElement.prototype.play.chatAskPatched = true;
+    }
+  }
+
+  // The model switcher's label, e.g. `ChatGPT 4o`
+  static async currentModel() {
+    const [btn] = await ChatAsk.waitFor(ChatAsk.SELECTORS.modelSwitcher, 5000);
+    if (!btn) throw new Error('The page has no model switcher');
+    return btn.innerText.trim();
+  }
+
+  // Picks the option labelled name, ignoring case, or else the only one containing it.
+  // Resolves to the option's label, rejects with the offered labels when none matches.
+  static async selectModel(name) {
+    const [btn] = await ChatAsk.waitFor(ChatAsk.SELECTORS.modelSwitcher, 5000);
+    if (!btn) throw new Error('The page has no model switcher');
+    // The switcher is a Radix menu, which opens on pointerdown rather than click
+    btn.dispatchEvent(new PointerEvent('pointerdown', { bubbles: true, button: 0, pointerType: 'mouse' }));
+    const options = await ChatAsk.waitFor(ChatAsk.SELECTORS.modelOption, 2000);
+    if (!options.length) throw new Error('The model menu did not open');
+
+    const labels = options.map((option) => option.innerText.split('\n')[0].trim());
+    const wanted = name.trim().toLowerCase();
+    let index = labels.findIndex((label) => label.toLowerCase() === wanted);
+    if (index < 0) {
+      const partial = labels.filter((label) => label.toLowerCase().includes(wanted));
+      if (partial.length === 1) index = labels.indexOf(partial[0]);
+    }
+    if (index < 0) {
+      document.dispatchEvent(new KeyboardEvent('keydown', { key: 'Escape', bubbles: true }));
+      throw new Error(`No model "${name}" in the list, available: ${labels.join(', ')}`);
+    }
+    options[index].click();
+    return labels[index];
+  }
+
+  static focus() {
+    ChatAsk.composer()?.focus();
+  }
+
+  static lastResponse() {
+    const messages = ChatAsk.queryAll(ChatAsk.SELECTORS.assistantMessage);
+    const last = messages[messages.length - 1];
+    return last ? last.innerText.trim() : '';
+  }
+};
@@ -1,20 +1,102 @@
-use tauri::{command, AppHandle, LogicalPosition, Manager, PhysicalSize};
+use log::{error, warn};
+use serde::Serialize;
+use std::{
+    path::PathBuf,
+    sync::atomic::{AtomicBool, Ordering},
+    time::{SystemTime, UNIX_EPOCH},
+};
+use tauri::{
+    command, AppHandle, Emitter, LogicalSize, Manager, PhysicalPosition, PhysicalSize, Theme, Url,
+    Webview, Window, Wry,
+};
+use tauri_plugin_clipboard_manager::ClipboardExt;
+use tauri_plugin_dialog::{DialogExt, FileDialogBuilder};
+use tauri_plugin_shell::ShellExt;
 
 use crate::core::{
-    conf::AppConf,
-    constant::{ASK_HEIGHT, TITLEBAR_HEIGHT},
+    conf::{is_valid_profile_name, AppConf},
+    constant::{
+        COPY_LAST_RESPONSE_SCRIPT, DEFAULT_WINDOW_TITLE, FOCUS_COMPOSER_SCRIPT, OPACITY_RANGE,
+        SAFE_MODE_FLAG, SPLIT_RATIO_RANGE, STOP_GENERATING_SCRIPT, TOGGLE_SIDEBAR_SCRIPT,
+        ZOOM_RANGE, ZOOM_STEP,
+    },
+    crash, download, menu, navigation, platform,
+    setup::{self, ask_view, reference_view, titlebar_view, update_view_positions, ViewLayout},
 };
 
+/// Reloads the main view of the window the titlebar belongs to.
 #[command]
-pub fn view_reload(app: AppHandle) {
-    app.get_window("core")
-        .unwrap()
-        .get_webview("main")
+pub fn view_reload(window: Window) {
+    window
+        .get_webview(&setup::view_label(window.label(), "main"))
         .unwrap()
         .eval("window.location.reload()")
         .unwrap();
 }
 
+/// Navigates the main view back to the configured `start_url`.
+#[command]
+pub fn reload_main(app: AppHandle) -> Result<(), String> {
+    let mut main_view = main_view(&app)?;
+    let start_url = AppConf::load(&app)
+        .map_err(|e| format!("[cmd:reload_main] Failed to load config: {}", e))?
+        .start_url();
+    main_view
+        .navigate(start_url)
+        .map_err(|e| format!("[cmd:reload_main] Failed to navigate: {}", e))
+}
+
+/// The URL the main view is currently showing. Changes are announced with
+/// `main-url-changed`.
+#[command]
+pub fn get_main_url(app: AppHandle) -> Result<String, String> {
+    main_view(&app)?
+        .url()
+        .map(String::from)
+        .map_err(|e| format!("[cmd:get_main_url] {}", e))
+}
+
+/// Navigates the main view to `new_chat_path` on the configured `start_url`.
+#[command]
+pub fn new_chat(app: AppHandle) -> Result<(), String> {
+    let mut main_view = main_view(&app)?;
+    let conf =
+        AppConf::load(&app).map_err(|e| format!("[cmd:new_chat] Failed to load config: {}", e))?;
+    let new_chat_url = conf
+        .start_url()
+        .join(&conf.new_chat_path)
+        .map_err(|e| format!("[cmd:new_chat] Invalid new_chat_path: {}", e))?;
+    main_view
+        .navigate(new_chat_url)
+        .map_err(|e| format!("[cmd:new_chat] Failed to navigate: {}", e))
+}
+
+/// Prints the main view to a PDF at `path`, by default under a timestamped name
+/// in the download folder. Finishes like a download, see `download::complete`,
+/// with `download-finished` carrying the path.
+#[command]
+pub async fn export_conversation_pdf(
+    app: AppHandle,
+    path: Option<String>,
+) -> Result<PathBuf, String> {
+    let path = match path {
+        Some(path) => PathBuf::from(path),
+        None => export_path(&app, "pdf")
+            .ok_or("[cmd:export_conversation_pdf] There is no download directory")?,
+    };
+
+    let result = platform::print_pdf(&main_view(&app)?, &path).await;
+    download::complete(&app, "titlebar", path.clone(), result.is_ok());
+    result.map_err(|e| {
+        format!(
+            "[cmd:export_conversation_pdf] Failed to write {}: {}",
+            path.display(),
+            e
+        )
+    })?;
+    Ok(path)
+}
+
 #[command]
 pub fn view_url(app: AppHandle) -> tauri::Url {
     app.get_window("core")
@@ -26,49 +108,438 @@ pub fn view_url(app: AppHandle) -> tauri::Url {
 }
 
 #[command]
-pub fn view_go_forward(app: AppHandle) {
-    app.get_window("core")
-        .unwrap()
-        .get_webview("main")
-        .unwrap()
-        .eval("window.history.forward()")
-        .unwrap();
+pub fn window_pin(app: AppHandle, pin: bool) -> Result<(), String> {
+    set_always_on_top(&app, pin)
 }
 
+/// Flips and persists `stay_on_top`, returning the new state.
 #[command]
-pub fn view_go_back(app: AppHandle) {
-    app.get_window("core")
-        .unwrap()
-        .get_webview("main")
-        .unwrap()
-        .eval("window.history.back()")
-        .unwrap();
+pub fn toggle_always_on_top(app: AppHandle) -> Result<bool, String> {
+    let pin = !AppConf::load(&app)
+        .map_err(|e| format!("[cmd:toggle_always_on_top] Failed to load config: {}", e))?
+        .stay_on_top;
+    set_always_on_top(&app, pin)?;
+    Ok(pin)
 }
 
+/// Titles the calling window after the open conversation through `title_template`,
+/// an empty `title` restores the default one.
 #[command]
-pub fn window_pin(app: AppHandle, pin: bool) {
-    let conf = AppConf::load(&app).unwrap();
-    conf.amend(serde_json::json!({"stay_on_top": pin}))
-        .unwrap()
-        .save(&app)
-        .unwrap();
+pub fn set_window_title(window: Window, title: String) -> Result<(), String> {
+    let title = title.trim();
+    let title = if title.is_empty() {
+        DEFAULT_WINDOW_TITLE.to_string()
+    } else {
+        AppConf::load(window.app_handle())
+            .map_err(|e| format!("[cmd:set_window_title] Failed to load config: {}", e))?
+            .title_template
+            .replace("{chat}", title)
+    };
+    window
+        .set_title(&title)
+        .map_err(|e| format!("[cmd:set_window_title] {}", e))
+}
+
+/// Resizes the core window to `width`×`height` logical pixels and saves it as
+/// the window size. Sizes below the minimum window size are refused.
+#[command]
+pub fn resize_window(app: AppHandle, width: f64, height: f64) -> Result<(), String> {
+    set_window_size(&app, width, height).map_err(|e| format!("[cmd:resize_window] {}", e))
+}
+
+/// Resizes the core window to one of the `window_presets`.
+#[command]
+pub fn resize_preset(app: AppHandle, name: String) -> Result<(), String> {
+    let conf = AppConf::load(&app)
+        .map_err(|e| format!("[cmd:resize_preset] Failed to load config: {}", e))?;
+    let preset = conf
+        .window_presets
+        .get(&name)
+        .ok_or_else(|| format!("[cmd:resize_preset] Unknown preset `{}`", name))?;
+    set_window_size(&app, preset.width, preset.height)
+        .map_err(|e| format!("[cmd:resize_preset] {}", e))
+}
+
+/// Snaps the core window to the left half of its monitor's work area.
+#[command]
+pub fn snap_left(app: AppHandle) -> Result<(), String> {
+    snap_window(&app, |(x, y), (width, height), _| {
+        ((x, y), (width / 2, height))
+    })
+    .map_err(|e| format!("[cmd:snap_left] {}", e))
+}
+
+#[command]
+pub fn snap_right(app: AppHandle) -> Result<(), String> {
+    snap_window(&app, |(x, y), (width, height), _| {
+        let half = width / 2;
+        ((x + half as i32, y), (width - half, height))
+    })
+    .map_err(|e| format!("[cmd:snap_right] {}", e))
+}
+
+/// Fills the work area without maximizing, so the window can still be dragged.
+#[command]
+pub fn snap_full(app: AppHandle) -> Result<(), String> {
+    snap_window(&app, |position, size, _| (position, size))
+        .map_err(|e| format!("[cmd:snap_full] {}", e))
+}
+
+/// Centers the window in the work area, shrinking it if it doesn't fit.
+#[command]
+pub fn snap_center(app: AppHandle) -> Result<(), String> {
+    snap_window(
+        &app,
+        |(x, y), (width, height), (current_width, current_height)| {
+            let (snap_width, snap_height) = (current_width.min(width), current_height.min(height));
+            (
+                (
+                    x + ((width - snap_width) / 2) as i32,
+                    y + ((height - snap_height) / 2) as i32,
+                ),
+                (snap_width, snap_height),
+            )
+        },
+    )
+    .map_err(|e| format!("[cmd:snap_center] {}", e))
+}
+
+type Bounds = ((i32, i32), (u32, u32));
+
+/// Moves the core window to the outer bounds `f` picks from the work area of the
+/// monitor it is on and its current outer size, all in physical pixels.
+fn snap_window(
+    app: &AppHandle,
+    f: impl FnOnce((i32, i32), (u32, u32), (u32, u32)) -> Bounds,
+) -> Result<(), String> {
+    let conf = AppConf::load(app).map_err(|e| format!("Failed to load config: {}", e))?;
+    let win = app
+        .get_window("core")
+        .ok_or("The core window does not exist")?;
+    let (area_position, area_size) =
+        platform::work_area(&win).ok_or("The window is not on a monitor")?;
+    if win.is_maximized().unwrap_or(false) {
+        win.unmaximize().map_err(|e| e.to_string())?;
+    }
+
+    let outer = win.outer_size().map_err(|e| e.to_string())?;
+    let inner = win.inner_size().map_err(|e| e.to_string())?;
+    let ((x, y), (width, height)) = f(
+        (area_position.x, area_position.y),
+        (area_size.width, area_size.height),
+        (outer.width, outer.height),
+    );
+    // `set_size` takes the inner size, leave room for the frame
+    let scale_factor = win.scale_factor().map_err(|e| e.to_string())?;
+    let (min_width, min_height) = conf.min_window_size();
+    let size = PhysicalSize::new(
+        width
+            .saturating_sub(outer.width.saturating_sub(inner.width))
+            .max((min_width * scale_factor) as u32),
+        height
+            .saturating_sub(outer.height.saturating_sub(inner.height))
+            .max((min_height * scale_factor) as u32),
+    );
+
+    win.set_position(PhysicalPosition::new(x, y))
+        .and_then(|_| win.set_size(size))
+        .map_err(|e| e.to_string())?;
+    // Lay the views out right away instead of waiting for the resize event
+    update_view_positions(&win, &conf, size);
+    Ok(())
+}
+
+/// The monitor the core window is on, in physical pixels.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct DisplayInfo {
+    pub scale_factor: f64,
+    pub monitor_width: u32,
+    pub monitor_height: u32,
+    pub work_area: WorkArea,
+}
+
+/// The monitor's bounds minus the taskbar, dock and menu bar.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct WorkArea {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+#[command]
+pub fn get_display_info(app: AppHandle) -> Result<DisplayInfo, String> {
+    let win = app
+        .get_window("core")
+        .ok_or("[cmd:get_display_info] The core window does not exist")?;
+    display_info(&win).map_err(|e| format!("[cmd:get_display_info] {}", e))
+}
+
+/// Also used by the window's move and scale handlers to emit `display-changed`.
+pub fn display_info(win: &Window) -> Result<DisplayInfo, String> {
+    let monitor = win
+        .current_monitor()
+        .map_err(|e| e.to_string())?
+        .ok_or("The window is not on a monitor")?;
+    let (position, size) =
+        platform::work_area(win).unwrap_or((*monitor.position(), *monitor.size()));
+    Ok(DisplayInfo {
+        scale_factor: monitor.scale_factor(),
+        monitor_width: monitor.size().width,
+        monitor_height: monitor.size().height,
+        work_area: WorkArea {
+            x: position.x,
+            y: position.y,
+            width: size.width,
+            height: size.height,
+        },
+    })
+}
+
+fn set_window_size(app: &AppHandle, width: f64, height: f64) -> Result<(), String> {
+    let conf = AppConf::load(app).map_err(|e| format!("Failed to load config: {}", e))?;
+    let (min_width, min_height) = conf.min_window_size();
+    // Also refuses NaN
+    if !(width >= min_width && height >= min_height && width.is_finite() && height.is_finite()) {
+        return Err(format!(
+            "{}x{} is below the minimum window size of {}x{}",
+            width, height, min_width, min_height
+        ));
+    }
+
+    let win = app
+        .get_window("core")
+        .ok_or("The core window does not exist")?;
+    if win.is_maximized().unwrap_or(false) {
+        win.unmaximize().map_err(|e| e.to_string())?;
+    }
+    let size = LogicalSize::new(width, height);
+    win.set_size(size).map_err(|e| e.to_string())?;
+    // Lay the views out right away instead of waiting for the resize event
+    let scale_factor = win.scale_factor().map_err(|e| e.to_string())?;
+    update_view_positions(&win, &conf, size.to_physical::<u32>(scale_factor));
+
+    conf.amend(serde_json::json!({ "window_width": width, "window_height": height }))
+        .map_err(|e| e.into())
+        .and_then(|conf| conf.save(app))
+        .map_err(|e| format!("Failed to save config: {}", e))
+}
+
+/// Applies `stay_on_top` to the core window and keeps the View menu check item
+/// and the titlebar pin in sync.
+fn set_always_on_top(app: &AppHandle, pin: bool) -> Result<(), String> {
+    AppConf::load(app)
+        .and_then(|conf| Ok(conf.amend(serde_json::json!({ "stay_on_top": pin }))?))
+        .and_then(|conf| conf.save(app))
+        .map_err(|e| format!("[cmd:always_on_top] Failed to save config: {}", e))?;
 
     app.get_window("core")
-        .unwrap()
+        .ok_or("[cmd:always_on_top] The core window does not exist")?
         .set_always_on_top(pin)
-        .unwrap();
+        .map_err(|e| format!("[cmd:always_on_top] {}", e))?;
+
+    menu::set_checked(app, "view_always_on_top", pin)
+        .and_then(|_| app.emit("always-on-top-changed", pin))
+        .map_err(|e| format!("[cmd:always_on_top] {}", e))
 }
 
+/// Flips and persists `visible_on_all_workspaces`, returning the new state.
+/// Windows has no such API, there it only logs and stays off.
 #[command]
-pub fn ask_sync(app: AppHandle, message: String) {
+pub fn toggle_visible_on_all_workspaces(app: AppHandle) -> Result<bool, String> {
+    if cfg!(windows) {
+        warn!(
+            "[cmd:visible_on_all_workspaces] Showing on all workspaces is unsupported on Windows"
+        );
+        return Ok(false);
+    }
+
+    let visible = !AppConf::load(&app)
+        .map_err(|e| {
+            format!(
+                "[cmd:visible_on_all_workspaces] Failed to load config: {}",
+                e
+            )
+        })?
+        .visible_on_all_workspaces;
+    AppConf::load(&app)
+        .and_then(|conf| {
+            Ok(conf.amend(serde_json::json!({ "visible_on_all_workspaces": visible }))?)
+        })
+        .and_then(|conf| conf.save(&app))
+        .map_err(|e| {
+            format!(
+                "[cmd:visible_on_all_workspaces] Failed to save config: {}",
+                e
+            )
+        })?;
+
     app.get_window("core")
-        .unwrap()
-        .get_webview("main")
+        .ok_or("[cmd:visible_on_all_workspaces] The core window does not exist")?
+        .set_visible_on_all_workspaces(visible)
+        .map_err(|e| format!("[cmd:visible_on_all_workspaces] {}", e))?;
+    menu::set_checked(&app, "view_all_workspaces", visible)
+        .map_err(|e| format!("[cmd:visible_on_all_workspaces] {}", e))?;
+    Ok(visible)
+}
+
+#[command]
+pub fn ask_sync(window: Window, message: String) {
+    window
+        .get_webview(&setup::view_label(window.label(), "main"))
         .unwrap()
         .eval(&format!("ChatAsk.sync({})", message))
         .unwrap();
 }
 
+/// Fills the composer of the calling window's main view with `prompt` and submits
+/// it. The DOM selectors live in `ChatAsk.SELECTORS` in ask.js.
+#[command]
+pub fn submit_ask(window: Window, prompt: String) -> Result<(), String> {
+    let prompt = serde_json::to_string(&prompt).map_err(|e| format!("[cmd:submit_ask] {}", e))?;
+    window
+        .get_webview(&setup::view_label(window.label(), "main"))
+        .ok_or("[cmd:submit_ask] The main webview does not exist")?
+        .eval(&format!("ChatAsk.send({})", prompt))
+        .map_err(|e| format!("[cmd:submit_ask] {}", e))?;
+    clear_ask(window)
+}
+
+/// Empties the calling window's ask strip and focuses it again. Does nothing
+/// when ask mode is off and the strip doesn't exist.
+#[command]
+pub fn clear_ask(window: Window) -> Result<(), String> {
+    let label = setup::view_label(window.label(), "ask");
+    match window.get_webview(&label) {
+        Some(view) => window
+            .emit_to(&label, "ask:clear", ())
+            .and_then(|_| view.set_focus())
+            .map_err(|e| format!("[cmd:clear_ask] {}", e)),
+        None => Ok(()),
+    }
+}
+
+/// Focuses the main view and its prompt, doing nothing on pages without one.
+#[command]
+pub fn focus_composer(app: AppHandle) -> Result<(), String> {
+    let main_view = main_view(&app)?;
+    main_view
+        .set_focus()
+        .and_then(|_| main_view.eval(FOCUS_COMPOSER_SCRIPT))
+        .map_err(|e| format!("[cmd:focus_composer] {}", e))
+}
+
+/// Clicks ChatGPT's stop button, doing nothing when no response is being generated.
+#[command]
+pub fn stop_generating(app: AppHandle) -> Result<(), String> {
+    main_view(&app)?
+        .eval(STOP_GENERATING_SCRIPT)
+        .map_err(|e| format!("[cmd:stop_generating] {}", e))
+}
+
+/// Clicks ChatGPT's sidebar toggle, which reports the new state back to
+/// `sidebar_toggled`. Pages without a sidebar are left alone.
+#[command]
+pub fn toggle_sidebar(app: AppHandle) -> Result<(), String> {
+    main_view(&app)?
+        .eval(TOGGLE_SIDEBAR_SCRIPT)
+        .map_err(|e| format!("[cmd:toggle_sidebar] {}", e))
+}
+
+/// Persists `sidebar_collapsed` so the main view collapses it again after a reload.
+#[command]
+pub fn sidebar_toggled(app: AppHandle, collapsed: bool) -> Result<(), String> {
+    AppConf::load(&app)
+        .and_then(|conf| Ok(conf.amend(serde_json::json!({ "sidebar_collapsed": collapsed }))?))
+        .and_then(|conf| conf.save(&app))
+        .map_err(|e| format!("[cmd:sidebar_toggled] {}", e))
+}
+
+/// Flips and persists `muted`, then mutes or unmutes the media of every window's
+/// main view, since they all follow the setting, and emits `mute-changed`.
+/// Returns the new state.
+#[command]
+pub fn toggle_mute(app: AppHandle) -> Result<bool, String> {
+    let conf = AppConf::load(&app)
+        .map_err(|e| format!("[cmd:toggle_mute] Failed to load config: {}", e))?;
+    let muted = !conf.muted;
+    conf.amend(serde_json::json!({ "muted": muted }))
+        .map_err(|e| format!("[cmd:toggle_mute] {}", e))?
+        .save(&app)
+        .map_err(|e| format!("[cmd:toggle_mute] Failed to save config: {}", e))?;
+
+    for view in app.webviews().into_values() {
+        if view.label() == setup::view_label(view.window().label(), "main") {
+            view.eval(&set_muted_script(muted))
+                .map_err(|e| format!("[cmd:toggle_mute] {}", e))?;
+        }
+    }
+    app.emit("mute-changed", muted)
+        .map_err(|e| format!("[cmd:toggle_mute] {}", e))?;
+    Ok(muted)
+}
+
+/// Also evaluated by the main view after each page load while `muted` is set.
+pub fn set_muted_script(muted: bool) -> String {
+    format!("window.ChatAsk?.setMuted({});", muted)
+}
+
+/// Saves the main view as a PNG under a timestamped name in the download
+/// folder, then emits `screenshot-saved` with the path.
+#[command]
+pub async fn screenshot(app: AppHandle) -> Result<PathBuf, String> {
+    let path = export_path(&app, "png").ok_or("[cmd:screenshot] There is no download directory")?;
+
+    let png = platform::capture_png(&main_view(&app)?)
+        .await
+        .map_err(|e| format!("[cmd:screenshot] Failed to capture the main view: {}", e))?;
+    std::fs::write(&path, png)
+        .map_err(|e| format!("[cmd:screenshot] Failed to write {}: {}", path.display(), e))?;
+
+    app.emit("screenshot-saved", serde_json::json!({ "path": path }))
+        .map_err(|e| format!("[cmd:screenshot] {}", e))?;
+    Ok(path)
+}
+
+/// Reads the last assistant message from the main view, which hands it back to
+/// `copy_response_text` since `eval` can't return values.
+#[command]
+pub fn copy_last_response(app: AppHandle) -> Result<(), String> {
+    main_view(&app)?
+        .eval(COPY_LAST_RESPONSE_SCRIPT)
+        .map_err(|e| format!("[cmd:copy_last_response] {}", e))
+}
+
+/// Writes `text` to the clipboard, or emits a `toast` when there was no response.
+#[command]
+pub fn copy_response_text(app: AppHandle, text: Option<String>) -> Result<(), String> {
+    let Some(text) = text else {
+        return app
+            .emit(
+                "toast",
+                serde_json::json!({ "message": "No response to copy" }),
+            )
+            .map_err(|e| format!("[cmd:copy_response_text] {}", e));
+    };
+    app.clipboard()
+        .write_text(text)
+        .map_err(|e| format!("[cmd:copy_response_text] {}", e))
+}
+
+/// Inserts the clipboard's text into the composer at the cursor, without the
+/// formatting a regular paste carries. Does nothing when the clipboard holds no text.
+#[command]
+pub fn paste_plain(app: AppHandle) -> Result<(), String> {
+    let text = match app.clipboard().read_text() {
+        Ok(text) if !text.is_empty() => text,
+        _ => return Ok(()),
+    };
+    let text = serde_json::to_string(&text).map_err(|e| format!("[cmd:paste_plain] {}", e))?;
+    main_view(&app)?
+        .eval(&format!("window.ChatAsk?.insert({})", text))
+        .map_err(|e| format!("[cmd:paste_plain] {}", e))
+}
+
 #[command]
 pub fn ask_send(app: AppHandle) {
     let win = app.get_window("core").unwrap();
@@ -86,15 +557,150 @@ pub fn ask_send(app: AppHandle) {
         .unwrap();
 }
 
+/// Persists `theme` and notifies the webviews so they restyle without a restart.
+///
+/// Tauri has no runtime `Window::set_theme` yet, so the native window chrome only
+/// follows the new theme on the next launch.
 #[command]
-pub fn set_theme(app: AppHandle, theme: String) {
-    let conf = AppConf::load(&app).unwrap();
-    conf.amend(serde_json::json!({"theme": theme}))
-        .unwrap()
-        .save(&app)
-        .unwrap();
+pub fn set_theme(app: AppHandle, webview: Webview, theme: String) -> Result<(), String> {
+    require_local_view(&webview, "set_theme")?;
+    if !matches!(theme.as_str(), "light" | "dark" | "system") {
+        return Err(format!("[cmd:set_theme] Unknown theme `{}`", theme));
+    }
+    AppConf::load(&app)
+        .and_then(|conf| Ok(conf.amend(serde_json::json!({ "theme": theme }))?))
+        .and_then(|conf| conf.save(&app))
+        .map_err(|e| format!("[cmd:set_theme] Failed to save config: {}", e))?;
+    emit_theme_changed(&app).map_err(|e| format!("[cmd:set_theme] {}", e))
+}
+
+/// Emits `theme-changed` with the configured theme and the light/dark value it
+/// resolves to.
+pub fn emit_theme_changed(app: &AppHandle) -> tauri::Result<()> {
+    let theme = AppConf::load(app)
+        .map(|conf| conf.theme)
+        .unwrap_or_else(|_| "system".to_string());
+    let resolved = match AppConf::get_theme(app) {
+        Theme::Dark => "dark",
+        _ => "light",
+    };
+    app.emit(
+        "theme-changed",
+        serde_json::json!({ "theme": theme, "resolved": resolved }),
+    )
+}
+
+/// Opens the scripts folder in the system file manager.
+#[command]
+pub fn open_scripts_folder(app: AppHandle) -> Result<(), String> {
+    let scripts_dir = AppConf::get_scripts_path(&app)
+        .map_err(|e| format!("[cmd:open_scripts_folder] Failed to resolve path: {}", e))?;
+    app.shell()
+        .open(scripts_dir.to_string_lossy(), None)
+        .map_err(|e| format!("[cmd:open_scripts_folder] {}", e))
+}
+
+#[command]
+pub fn list_script_presets(app: AppHandle) -> Result<Vec<String>, String> {
+    AppConf::list_script_presets(&app).map_err(|e| format!("[cmd:list_script_presets] {}", e))
+}
 
-    app.restart();
+/// Persists `script_preset` and recreates the main view at its current URL, since
+/// initialization scripts are fixed when a webview is created. An empty `name`
+/// goes back to the shared scripts only.
+#[command]
+pub fn apply_script_preset(app: AppHandle, webview: Webview, name: String) -> Result<(), String> {
+    require_local_view(&webview, "apply_script_preset")?;
+    let presets = AppConf::list_script_presets(&app)
+        .map_err(|e| format!("[cmd:apply_script_preset] {}", e))?;
+    if !name.is_empty() && !presets.contains(&name) {
+        return Err(format!(
+            "[cmd:apply_script_preset] Unknown preset `{}`",
+            name
+        ));
+    }
+    let preset = (!name.is_empty()).then_some(name);
+    let conf = AppConf::load(&app)
+        .and_then(|conf| Ok(conf.amend(serde_json::json!({ "script_preset": preset }))?))
+        .map_err(|e| format!("[cmd:apply_script_preset] Failed to load config: {}", e))?;
+    conf.save(&app)
+        .map_err(|e| format!("[cmd:apply_script_preset] Failed to save config: {}", e))?;
+    let url = navigation::current(&app).unwrap_or_else(|| conf.start_url());
+    recreate_main_view(&app, &conf, url).map_err(|e| format!("[cmd:apply_script_preset] {}", e))
+}
+
+/// Writes the current settings as JSON to a file the user picks in a save
+/// dialog, for `import_settings`. Returns the path, `None` when cancelled.
+#[command]
+pub async fn export_settings(app: AppHandle, webview: Webview) -> Result<Option<PathBuf>, String> {
+    require_local_view(&webview, "export_settings")?;
+    let conf = AppConf::load(&app).map_err(|e| format!("[cmd:export_settings] {}", e))?;
+    let Some(path) = settings_dialog(&app)
+        .set_file_name("ChatGPT-settings.json")
+        .blocking_save_file()
+    else {
+        return Ok(None);
+    };
+    let contents =
+        serde_json::to_string_pretty(&conf).map_err(|e| format!("[cmd:export_settings] {}", e))?;
+    std::fs::write(&path, contents).map_err(|e| {
+        format!(
+            "[cmd:export_settings] Failed to write {}: {}",
+            path.display(),
+            e
+        )
+    })?;
+    Ok(Some(path))
+}
+
+/// Replaces the settings with the ones exported to a file the user picks,
+/// keeping this machine's `data_dir`. Theme, always on top, opacity and zoom are
+/// applied right away, the rest when the views are next created. Returns `None`
+/// when the dialog is cancelled.
+#[command]
+pub async fn import_settings(app: AppHandle, webview: Webview) -> Result<Option<AppConf>, String> {
+    require_local_view(&webview, "import_settings")?;
+    let current = AppConf::load(&app).map_err(|e| format!("[cmd:import_settings] {}", e))?;
+    let Some(file) = settings_dialog(&app).blocking_pick_file() else {
+        return Ok(None);
+    };
+    let mut conf =
+        AppConf::from_file(&file.path).map_err(|e| format!("[cmd:import_settings] {}", e))?;
+    conf.data_dir = current.data_dir;
+    conf.save(&app)
+        .map_err(|e| format!("[cmd:import_settings] Failed to save config: {}", e))?;
+    apply_settings(&app, &conf).map_err(|e| format!("[cmd:import_settings] {}", e))?;
+    Ok(Some(conf))
+}
+
+/// File dialog for settings files, starting in the download folder.
+fn settings_dialog(app: &AppHandle) -> FileDialogBuilder<Wry> {
+    let dialog = app
+        .dialog()
+        .file()
+        .set_title(DEFAULT_WINDOW_TITLE)
+        .add_filter("Settings", &["json"]);
+    match download::download_dir(app) {
+        Some(dir) => dialog.set_directory(dir),
+        None => dialog,
+    }
+}
+
+/// Applies the settings that can change without recreating a view: theme,
+/// always on top, opacity and the main view's zoom.
+fn apply_settings(app: &AppHandle, conf: &AppConf) -> Result<(), String> {
+    setup::allow_start_url(app, conf);
+    emit_theme_changed(app).map_err(|e| e.to_string())?;
+    set_always_on_top(app, conf.stay_on_top)?;
+    if let Some(core_window) = app.get_window("core") {
+        platform::set_opacity(&core_window, conf.opacity).map_err(|e| e.to_string())?;
+    }
+    if let Ok(view) = main_view(app) {
+        let zoom = view.url().map(|url| conf.zoom_for(&url)).unwrap_or(1.0);
+        view.set_zoom(zoom)
+            .map_err(|e| format!("Failed to set zoom: {}", e))?;
+    }
+    Ok(())
 }
 
 #[command]
@@ -102,91 +708,617 @@ pub fn get_app_conf(app: AppHandle) -> AppConf {
     AppConf::load(&app).unwrap()
 }
 
+/// The whole config, for settings UIs that handle fields generically.
 #[command]
-pub fn set_view_ask(app: AppHandle, enabled: bool) {
-    let conf = AppConf::load(&app).unwrap();
-    conf.amend(serde_json::json!({"ask_mode": enabled}))
-        .unwrap()
-        .save(&app)
-        .unwrap();
+pub fn get_config(app: AppHandle) -> Result<AppConf, String> {
+    AppConf::load(&app).map_err(|e| format!("[cmd:get_config] {}", e))
+}
 
-    let core_window = app.get_window("core").unwrap();
-    let ask_mode_height = if enabled { ASK_HEIGHT } else { 0.0 };
-    let scale_factor = core_window.scale_factor().unwrap();
-    let titlebar_height = (scale_factor * TITLEBAR_HEIGHT).round() as u32;
-    let win_size = core_window.inner_size().unwrap();
-    let ask_height = (scale_factor * ask_mode_height).round() as u32;
+/// Saves the fields in `json` over the current config and returns the result.
+/// Unknown fields and invalid values are refused and nothing is saved. Theme,
+/// always on top, opacity and zoom apply right away, the rest when the views
+/// are next created. Only the app's own views may call it.
+#[command]
+pub fn update_config(
+    app: AppHandle,
+    webview: Webview,
+    json: serde_json::Value,
+) -> Result<AppConf, String> {
+    require_local_view(&webview, "update_config")?;
+    let conf = AppConf::load(&app)
+        .map_err(|e| format!("[cmd:update_config] Failed to load config: {}", e))?
+        .amend_checked(json)
+        .map_err(|e| format!("[cmd:update_config] {}", e))?;
+    conf.save(&app)
+        .map_err(|e| format!("[cmd:update_config] Failed to save config: {}", e))?;
+    apply_settings(&app, &conf).map_err(|e| format!("[cmd:update_config] {}", e))?;
+    Ok(conf)
+}
 
-    let main_view = core_window.get_webview("main").unwrap();
-    let titlebar_view = core_window.get_webview("titlebar").unwrap();
-    let ask_view = core_window.get_webview("ask").unwrap();
+/// Persists `ask_mode`, creates or closes the calling window's ask strip and
+/// re-runs its view layout, so the strip appears or disappears without a restart.
+#[command]
+pub fn set_ask_mode(window: Window, webview: Webview, enabled: bool) -> Result<(), String> {
+    require_local_view(&webview, "set_ask_mode")?;
+    let app = window.app_handle();
+    let conf = AppConf::load(app)
+        .and_then(|conf| Ok(conf.amend(serde_json::json!({ "ask_mode": enabled }))?))
+        .map_err(|e| format!("[cmd:set_ask_mode] Failed to load config: {}", e))?;
+    conf.save(app)
+        .map_err(|e| format!("[cmd:set_ask_mode] Failed to save config: {}", e))?;
 
-    if enabled {
-        ask_view.set_focus().unwrap();
-    } else {
-        main_view.set_focus().unwrap();
+    let scale_factor = window
+        .scale_factor()
+        .map_err(|e| format!("[cmd:set_ask_mode] {}", e))?;
+    let win_size = window
+        .inner_size()
+        .map_err(|e| format!("[cmd:set_ask_mode] {}", e))?;
+    let ask_label = setup::view_label(window.label(), "ask");
+
+    match (enabled, window.get_webview(&ask_label)) {
+        (true, None) => {
+            let layout = ViewLayout::new(
+                win_size,
+                scale_factor,
+                conf.titlebar_mode_height(),
+                conf.ask_mode_height(),
+                conf.titlebar_position,
+                conf.split_mode_ratio(),
+                conf.ask_floats(),
+            );
+            let (position, size) = layout.ask;
+            window
+                .add_child(ask_view(window.label(), &conf), position, size)
+                .map_err(|e| format!("[cmd:set_ask_mode] Failed to add ask view: {}", e))?;
+        }
+        (false, Some(view)) => view
+            .close()
+            .map_err(|e| format!("[cmd:set_ask_mode] Failed to close ask view: {}", e))?,
+        _ => (),
     }
 
-    let set_view_properties =
-        |view: &tauri::Webview, position: LogicalPosition<f64>, size: PhysicalSize<u32>| {
-            if let Err(e) = view.set_position(position) {
-                eprintln!("[cmd:view:position] Failed to set view position: {}", e);
-            }
-            if let Err(e) = view.set_size(size) {
-                eprintln!("[cmd:view:size] Failed to set view size: {}", e);
+    let focus_view = setup::view_label(window.label(), if enabled { "ask" } else { "main" });
+    if let Some(view) = window.get_webview(&focus_view) {
+        if let Err(e) = view.set_focus() {
+            error!("[cmd:set_ask_mode] Failed to focus {}: {}", focus_view, e);
+        }
+    }
+
+    update_view_positions(&window, &conf, win_size);
+    Ok(())
+}
+
+/// Shows `url` in the reference pane right of the main view and turns
+/// `split_view` on. An already open pane is navigated instead of recreated.
+#[command]
+pub fn set_reference_url(app: AppHandle, webview: Webview, url: String) -> Result<(), String> {
+    require_local_view(&webview, "set_reference_url")?;
+    let parsed =
+        Url::parse(&url).map_err(|e| format!("[cmd:set_reference_url] Invalid url: {}", e))?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err(format!(
+            "[cmd:set_reference_url] Unsupported scheme: {}",
+            parsed.scheme()
+        ));
+    }
+    let conf = AppConf::load(&app)
+        .and_then(|conf| {
+            Ok(conf.amend(serde_json::json!({ "reference_url": url, "split_view": true }))?)
+        })
+        .map_err(|e| format!("[cmd:set_reference_url] Failed to load config: {}", e))?;
+    conf.save(&app)
+        .map_err(|e| format!("[cmd:set_reference_url] Failed to save config: {}", e))?;
+    sync_reference_view(&app, &conf).map_err(|e| format!("[cmd:set_reference_url] {}", e))
+}
+
+/// Persists `split_view`, opening the reference pane at `reference_url` or
+/// closing it and giving the main view the full width again.
+#[command]
+pub fn set_split_view(app: AppHandle, webview: Webview, enabled: bool) -> Result<(), String> {
+    require_local_view(&webview, "set_split_view")?;
+    let conf = AppConf::load(&app)
+        .and_then(|conf| Ok(conf.amend(serde_json::json!({ "split_view": enabled }))?))
+        .map_err(|e| format!("[cmd:set_split_view] Failed to load config: {}", e))?;
+    if enabled && conf.reference_url().is_none() {
+        return Err("[cmd:set_split_view] No reference url set, see set_reference_url".into());
+    }
+    conf.save(&app)
+        .map_err(|e| format!("[cmd:set_split_view] Failed to save config: {}", e))?;
+    sync_reference_view(&app, &conf).map_err(|e| format!("[cmd:set_split_view] {}", e))
+}
+
+/// Persists the share of the width kept by the main view and re-runs the layout.
+#[command]
+pub fn set_split_ratio(app: AppHandle, webview: Webview, ratio: f64) -> Result<f64, String> {
+    require_local_view(&webview, "set_split_ratio")?;
+    let (min, max) = SPLIT_RATIO_RANGE;
+    let ratio = ratio.clamp(min, max);
+    let conf = AppConf::load(&app)
+        .and_then(|conf| Ok(conf.amend(serde_json::json!({ "split_ratio": ratio }))?))
+        .map_err(|e| format!("[cmd:set_split_ratio] Failed to load config: {}", e))?;
+    conf.save(&app)
+        .map_err(|e| format!("[cmd:set_split_ratio] Failed to save config: {}", e))?;
+    sync_reference_view(&app, &conf).map_err(|e| format!("[cmd:set_split_ratio] {}", e))?;
+    Ok(ratio)
+}
+
+/// Creates, navigates or closes the reference pane to match `conf`, then lays
+/// out all views again.
+fn sync_reference_view(app: &AppHandle, conf: &AppConf) -> Result<(), String> {
+    let core_window = app
+        .get_window("core")
+        .ok_or("The core window does not exist")?;
+    let scale_factor = core_window.scale_factor().map_err(|e| e.to_string())?;
+    let win_size = core_window.inner_size().map_err(|e| e.to_string())?;
+
+    let url = conf.split_mode_ratio().and(conf.reference_url());
+    match (url, core_window.get_webview("reference")) {
+        (Some(url), None) => {
+            let layout = ViewLayout::new(
+                win_size,
+                scale_factor,
+                conf.titlebar_mode_height(),
+                conf.ask_mode_height(),
+                conf.titlebar_position,
+                conf.split_mode_ratio(),
+                conf.ask_floats(),
+            );
+            let (position, size) = layout.reference;
+            core_window
+                .add_child(reference_view(conf, url), position, size)
+                .map_err(|e| format!("Failed to add reference view: {}", e))?;
+            raise_floating_ask(&core_window, conf, &layout)?;
+        }
+        (Some(url), Some(mut view)) => {
+            if view.url().ok().as_ref() != Some(&url) {
+                view.navigate(url)
+                    .map_err(|e| format!("Failed to navigate reference view: {}", e))?;
             }
-        };
+        }
+        (None, Some(view)) => view
+            .close()
+            .map_err(|e| format!("Failed to close reference view: {}", e))?,
+        (None, None) => (),
+    }
+
+    update_view_positions(&core_window, conf, win_size);
+    Ok(())
+}
+
+/// Flips and persists `custom_titlebar`, swapping between the custom titlebar
+/// view and the native titlebar. Returns the new state.
+#[command]
+pub fn toggle_titlebar(app: AppHandle, webview: Webview) -> Result<bool, String> {
+    require_local_view(&webview, "toggle_titlebar")?;
+    let conf = AppConf::load(&app)
+        .map_err(|e| format!("[cmd:toggle_titlebar] Failed to load config: {}", e))?;
+    let enabled = !conf.custom_titlebar;
+    let conf = conf
+        .amend(serde_json::json!({ "custom_titlebar": enabled }))
+        .map_err(|e| format!("[cmd:toggle_titlebar] {}", e))?;
+    conf.save(&app)
+        .map_err(|e| format!("[cmd:toggle_titlebar] Failed to save config: {}", e))?;
+
+    let core_window = app
+        .get_window("core")
+        .ok_or("[cmd:toggle_titlebar] The core window does not exist")?;
+    let scale_factor = core_window
+        .scale_factor()
+        .map_err(|e| format!("[cmd:toggle_titlebar] {}", e))?;
+    let win_size = core_window
+        .inner_size()
+        .map_err(|e| format!("[cmd:toggle_titlebar] {}", e))?;
 
     #[cfg(target_os = "macos")]
-    {
-        set_view_properties(
-            &main_view,
-            LogicalPosition::new(0.0, TITLEBAR_HEIGHT),
-            PhysicalSize::new(
-                win_size.width,
-                win_size.height - (titlebar_height + ask_height),
-            ),
-        );
-        set_view_properties(
-            &titlebar_view,
-            LogicalPosition::new(0.0, 0.0),
-            PhysicalSize::new(win_size.width, titlebar_height),
-        );
-        set_view_properties(
-            &ask_view,
-            LogicalPosition::new(
-                0.0,
-                (win_size.height as f64 / scale_factor) - ask_mode_height,
-            ),
-            PhysicalSize::new(win_size.width, ask_height),
-        );
+    core_window
+        .set_title_bar_style(if enabled {
+            tauri::TitleBarStyle::Overlay
+        } else {
+            tauri::TitleBarStyle::Visible
+        })
+        .map_err(|e| format!("[cmd:toggle_titlebar] {}", e))?;
+
+    match (enabled, core_window.get_webview("titlebar")) {
+        (true, None) => {
+            let layout = ViewLayout::new(
+                win_size,
+                scale_factor,
+                conf.titlebar_mode_height(),
+                conf.ask_mode_height(),
+                conf.titlebar_position,
+                conf.split_mode_ratio(),
+                conf.ask_floats(),
+            );
+            let (position, size) = layout.titlebar;
+            core_window
+                .add_child(titlebar_view("core", &conf), position, size)
+                .map_err(|e| format!("[cmd:toggle_titlebar] Failed to add titlebar: {}", e))?;
+        }
+        (false, Some(view)) => view
+            .close()
+            .map_err(|e| format!("[cmd:toggle_titlebar] Failed to close titlebar: {}", e))?,
+        _ => (),
     }
 
-    #[cfg(not(target_os = "macos"))]
-    {
-        set_view_properties(
-            &main_view,
-            LogicalPosition::new(0.0, 0.0),
-            PhysicalSize::new(
-                win_size.width,
-                win_size.height - (ask_height + titlebar_height),
-            ),
-        );
-        set_view_properties(
-            &titlebar_view,
-            LogicalPosition::new(
-                0.0,
-                (win_size.height as f64 / scale_factor) - TITLEBAR_HEIGHT,
-            ),
-            PhysicalSize::new(win_size.width, titlebar_height),
-        );
-        set_view_properties(
-            &ask_view,
-            LogicalPosition::new(
-                0.0,
-                (win_size.height as f64 / scale_factor) - ask_mode_height - TITLEBAR_HEIGHT,
-            ),
-            PhysicalSize::new(win_size.width, ask_height),
+    update_view_positions(&core_window, &conf, win_size);
+    Ok(enabled)
+}
+
+/// Clamps, persists and applies the window opacity, returning the applied value.
+#[command]
+pub fn set_opacity(app: AppHandle, webview: Webview, value: f64) -> Result<f64, String> {
+    require_local_view(&webview, "set_opacity")?;
+    let opacity = value.clamp(OPACITY_RANGE.0, OPACITY_RANGE.1);
+    AppConf::load(&app)
+        .and_then(|conf| Ok(conf.amend(serde_json::json!({ "opacity": opacity }))?))
+        .and_then(|conf| conf.save(&app))
+        .map_err(|e| format!("[cmd:set_opacity] Failed to save config: {}", e))?;
+
+    let core_window = app
+        .get_window("core")
+        .ok_or("[cmd:set_opacity] The core window does not exist")?;
+    platform::set_opacity(&core_window, opacity).map_err(|e| format!("[cmd:set_opacity] {}", e))?;
+    Ok(opacity)
+}
+
+/// Persists the active profile and recreates the main view on its data directory,
+/// so the profile's own cookies and sessions are used.
+#[command]
+pub fn switch_profile(app: AppHandle, webview: Webview, name: String) -> Result<(), String> {
+    require_local_view(&webview, "switch_profile")?;
+    if !is_valid_profile_name(&name) {
+        return Err(format!(
+            "[cmd:switch_profile] Invalid profile name `{}`",
+            name
+        ));
+    }
+    let conf = AppConf::load(&app)
+        .and_then(|conf| Ok(conf.amend(serde_json::json!({ "profile": name }))?))
+        .map_err(|e| format!("[cmd:switch_profile] Failed to load config: {}", e))?;
+    conf.save(&app)
+        .map_err(|e| format!("[cmd:switch_profile] Failed to save config: {}", e))?;
+    recreate_main_view(&app, &conf, conf.start_url())
+        .map_err(|e| format!("[cmd:switch_profile] {}", e))
+}
+
+/// Flips and persists `incognito`, then recreates the main view. Either way the
+/// current session is lost, which the titlebar is told about with a `toast`.
+#[command]
+pub fn toggle_incognito(app: AppHandle, webview: Webview) -> Result<bool, String> {
+    require_local_view(&webview, "toggle_incognito")?;
+    let conf = AppConf::load(&app)
+        .map_err(|e| format!("[cmd:toggle_incognito] Failed to load config: {}", e))?;
+    let enabled = !conf.incognito;
+    let conf = conf
+        .amend(serde_json::json!({ "incognito": enabled }))
+        .map_err(|e| format!("[cmd:toggle_incognito] {}", e))?;
+    conf.save(&app)
+        .map_err(|e| format!("[cmd:toggle_incognito] Failed to save config: {}", e))?;
+
+    let message = if enabled {
+        "Incognito on, you'll need to log in again and nothing will be kept"
+    } else {
+        "Incognito off, back to your saved session"
+    };
+    if let Err(e) = app.emit("toast", serde_json::json!({ "message": message })) {
+        error!("[cmd:toggle_incognito] {}", e);
+    }
+
+    recreate_main_view(&app, &conf, conf.start_url())
+        .map_err(|e| format!("[cmd:toggle_incognito] {}", e))?;
+    Ok(enabled)
+}
+
+/// Child views of the core window `toggle_devtools` can be pointed at.
+static DEVTOOLS_VIEWS: &[&str] = &["main", "titlebar", "ask"];
+
+/// Opens or closes DevTools on `view`, or without one on the focused webview. The
+/// core window has no way to tell which child has focus, so it resolves to the
+/// main view there.
+#[command]
+pub fn toggle_devtools(app: AppHandle, view: Option<String>) -> Result<(), String> {
+    let conf = AppConf::load(&app).map_err(|e| format!("[cmd:toggle_devtools] {}", e))?;
+    if !conf.enable_devtools && !cfg!(debug_assertions) {
+        return Err(
+            "[cmd:toggle_devtools] DevTools are disabled, set `enable_devtools` to use them".into(),
         );
     }
+
+    let view = match view {
+        Some(name) if !DEVTOOLS_VIEWS.contains(&name.as_str()) => {
+            return Err(format!(
+                "[cmd:toggle_devtools] Unknown view `{}`, expected one of {}",
+                name,
+                DEVTOOLS_VIEWS.join(", ")
+            ));
+        }
+        Some(name) => app
+            .get_webview(&name)
+            .ok_or_else(|| format!("[cmd:toggle_devtools] The {} view is not open", name))?,
+        None => {
+            let window = app
+                .get_focused_window()
+                .or_else(|| app.get_window("core"))
+                .ok_or("[cmd:toggle_devtools] No window to inspect")?;
+            match window.label() {
+                "core" => window.get_webview("main"),
+                _ => window.webviews().into_iter().next(),
+            }
+            .ok_or("[cmd:toggle_devtools] No webview to inspect")?
+        }
+    };
+
+    if view.is_devtools_open() {
+        view.close_devtools();
+    } else {
+        view.open_devtools();
+    }
+    Ok(())
+}
+
+/// What `clear_site_data` removed from the main view.
+#[derive(Serialize, Default)]
+pub struct ClearedSiteData {
+    /// `false` when the call only asked the titlebar to confirm.
+    pub confirmed: bool,
+    pub cookies: bool,
+    pub storage: bool,
+    pub cache: bool,
+    /// What was cleared beyond or short of the current profile's data.
+    pub note: Option<String>,
+}
+
+/// Set while `confirm-quit` waits for an answer, so a stray
+/// `confirm_quit_response` can't exit the app.
+static QUIT_PENDING: AtomicBool = AtomicBool::new(false);
+
+/// Starts the app again, in safe mode or out of it, see `SAFE_MODE_FLAG`. The
+/// other launch arguments are passed on.
+#[command]
+pub fn relaunch(app: AppHandle, safe_mode: bool) -> Result<(), String> {
+    let exe = std::env::current_exe().map_err(|e| format!("[cmd:relaunch] {}", e))?;
+    let mut args: Vec<String> = std::env::args()
+        .skip(1)
+        .filter(|arg| arg != SAFE_MODE_FLAG)
+        .collect();
+    if safe_mode {
+        args.push(SAFE_MODE_FLAG.to_string());
+    }
+    std::process::Command::new(exe)
+        .args(args)
+        .spawn()
+        .map_err(|e| format!("[cmd:relaunch] Failed to start the app: {}", e))?;
+    app.exit(0);
+    Ok(())
+}
+
+/// Exits the app. With `confirm_quit` the titlebar view is asked first and
+/// `confirm_quit_response` finishes the job.
+#[command]
+pub fn quit(app: AppHandle) -> Result<(), String> {
+    let confirm = AppConf::load(&app)
+        .map(|conf| conf.confirm_quit)
+        .unwrap_or(false);
+    if !confirm {
+        app.exit(0);
+        return Ok(());
+    }
+    let Some(titlebar) = app.get_webview("titlebar") else {
+        warn!("[cmd:quit] The titlebar webview is needed to confirm, quitting right away");
+        app.exit(0);
+        return Ok(());
+    };
+
+    QUIT_PENDING.store(true, Ordering::SeqCst);
+    // The window may be hidden in the tray
+    let core_window = app
+        .get_window("core")
+        .ok_or("[cmd:quit] The core window does not exist")?;
+    core_window
+        .show()
+        .and_then(|_| core_window.set_focus())
+        .and_then(|_| titlebar.set_focus())
+        .and_then(|_| app.emit_to("titlebar", "confirm-quit", ()))
+        .map_err(|e| format!("[cmd:quit] {}", e))
+}
+
+#[command]
+pub fn confirm_quit_response(app: AppHandle, confirmed: bool) {
+    if QUIT_PENDING.swap(false, Ordering::SeqCst) && confirmed {
+        app.exit(0);
+    }
+}
+
+/// Sets `first_run` again so the onboarding overlay is shown, right away when
+/// the titlebar is open, otherwise on the next start.
+#[command]
+pub fn reset_onboarding(app: AppHandle, webview: Webview) -> Result<(), String> {
+    require_local_view(&webview, "reset_onboarding")?;
+    AppConf::load(&app)
+        .and_then(|conf| Ok(conf.amend(serde_json::json!({ "first_run": true }))?))
+        .and_then(|conf| conf.save(&app))
+        .map_err(|e| format!("[cmd:reset_onboarding] {}", e))?;
+
+    setup::watch_first_run(&app);
+    if let Some(titlebar) = app.get_webview("titlebar") {
+        titlebar
+            .eval("location.reload()")
+            .map_err(|e| format!("[cmd:reset_onboarding] {}", e))?;
+    }
+    Ok(())
+}
+
+/// Asks the titlebar view to confirm clearing site data, the user is logged out.
+pub fn confirm_clear_site_data(app: &AppHandle) -> Result<(), String> {
+    app.get_webview("titlebar")
+        .ok_or("[cmd:clear_site_data] The titlebar webview is needed to confirm")?
+        .set_focus()
+        .and_then(|_| app.emit_to("titlebar", "clear-site-data-confirm", ()))
+        .map_err(|e| format!("[cmd:clear_site_data] {}", e))
+}
+
+/// Clears cookies, storage and cache of the main view and reopens it at the start
+/// URL. Without `confirmed` it only prompts for confirmation.
+#[command]
+pub async fn clear_site_data(app: AppHandle, confirmed: bool) -> Result<ClearedSiteData, String> {
+    if !confirmed {
+        confirm_clear_site_data(&app)?;
+        return Ok(ClearedSiteData::default());
+    }
+
+    let conf = AppConf::load(&app).map_err(|e| format!("[cmd:clear_site_data] {}", e))?;
+    let cleared = if conf.incognito {
+        // Nothing was kept on disk, the new view starts clean anyway
+        ClearedSiteData {
+            confirmed: true,
+            cookies: true,
+            storage: true,
+            cache: true,
+            note: None,
+        }
+    } else {
+        // Cleared through the live view, its engine holds the data in memory too
+        platform::clear_website_data(&main_view(&app)?, &conf.profile).await
+    };
+    if let Some(note) = &cleared.note {
+        warn!("[cmd:clear_site_data] {}", note);
+    }
+
+    recreate_main_view(&app, &conf, conf.start_url())
+        .map_err(|e| format!("[cmd:clear_site_data] {}", e))?;
+    Ok(cleared)
+}
+
+#[command]
+pub fn zoom_in(app: AppHandle) -> Result<f64, String> {
+    set_main_zoom(&app, |zoom| zoom + ZOOM_STEP)
+}
+
+#[command]
+pub fn zoom_out(app: AppHandle) -> Result<f64, String> {
+    set_main_zoom(&app, |zoom| zoom - ZOOM_STEP)
+}
+
+#[command]
+pub fn zoom_reset(app: AppHandle) -> Result<f64, String> {
+    set_main_zoom(&app, |_| 1.0)
+}
+
+/// Applies the zoom computed from the current one to the main view and persists
+/// it for the host being shown.
+fn set_main_zoom(app: &AppHandle, f: impl FnOnce(f64) -> f64) -> Result<f64, String> {
+    let mut conf =
+        AppConf::load(app).map_err(|e| format!("[cmd:zoom] Failed to load config: {}", e))?;
+    let view = main_view(app)?;
+    let url = view.url().map_err(|e| format!("[cmd:zoom] {}", e))?;
+    let (min, max) = ZOOM_RANGE;
+    // Round to avoid accumulating float error from repeated steps
+    let zoom = ((f(conf.zoom_for(&url)) * 100.0).round() / 100.0).clamp(min, max);
+
+    view.set_zoom(zoom)
+        .map_err(|e| format!("[cmd:zoom] Failed to set zoom: {}", e))?;
+    if let Some(host) = url.host_str() {
+        conf.host_zoom.insert(host.to_string(), zoom);
+        conf.save(app)
+            .map_err(|e| format!("[cmd:zoom] Failed to save config: {}", e))?;
+    }
+
+    Ok(zoom)
+}
+
+/// Replaces the main view with a fresh one at `url` built from `conf`, for
+/// settings that can only be applied when a webview is created.
+pub fn recreate_main_view(app: &AppHandle, conf: &AppConf, url: Url) -> Result<(), String> {
+    let core_window = app
+        .get_window("core")
+        .ok_or("The core window does not exist")?;
+    let scale_factor = core_window.scale_factor().map_err(|e| e.to_string())?;
+    let win_size = core_window.inner_size().map_err(|e| e.to_string())?;
+
+    if let Some(view) = core_window.get_webview("main") {
+        view.close()
+            .map_err(|e| format!("Failed to close main view: {}", e))?;
+    }
+    navigation::reset(app);
+
+    let layout = ViewLayout::new(
+        win_size,
+        scale_factor,
+        conf.titlebar_mode_height(),
+        conf.ask_mode_height(),
+        conf.titlebar_position,
+        conf.split_mode_ratio(),
+        conf.ask_floats(),
+    );
+    let (position, size) = layout.main;
+    let view = core_window
+        .add_child(setup::main_view(app, "core", conf, url), position, size)
+        .map_err(|e| format!("Failed to add main view: {}", e))?;
+    crash::watch(app, &view);
+    raise_floating_ask(&core_window, conf, &layout)?;
+    platform::set_background_color(&core_window, conf.background_color(AppConf::get_theme(app)))
+        .map_err(|e| format!("Failed to set background color: {}", e))
+}
+
+/// Child views stack in the order they are added, so a view added later covers
+/// the floating ask strip. Recreates the strip on top of it again.
+fn raise_floating_ask(win: &Window, conf: &AppConf, layout: &ViewLayout) -> Result<(), String> {
+    if !conf.ask_floats() {
+        return Ok(());
+    }
+    let Some(view) = win.get_webview("ask") else {
+        return Ok(());
+    };
+    view.close()
+        .map_err(|e| format!("Failed to close ask view: {}", e))?;
+    let (position, size) = layout.ask;
+    win.add_child(ask_view("core", conf), position, size)
+        .map(|_| ())
+        .map_err(|e| format!("Failed to add ask view: {}", e))
+}
+
+/// Opens another window with its own main, titlebar and ask views for a chat
+/// side by side, returning its label. The menu and most commands keep acting
+/// on the core window.
+#[command]
+pub async fn new_window(app: AppHandle) -> Result<String, String> {
+    let label = (2u32..)
+        .map(|n| format!("core-{}", n))
+        .find(|label| app.get_window(label).is_none())
+        .ok_or("[cmd:new_window] No free window label")?;
+    setup::create_main_window(&app, &label)
+        .map(|_| label)
+        .map_err(|e| format!("[cmd:new_window] {}", e))
+}
+
+/// Refuses `command` unless it comes from one of the app's own views. App
+/// commands are not ACL checked, so this keeps the remote page in the main view
+/// from changing settings such as `download_actions`.
+pub fn require_local_view(webview: &Webview, command: &str) -> Result<(), String> {
+    if setup::is_local_view(webview.label()) {
+        return Ok(());
+    }
+    warn!(
+        "[cmd:{}] Refused a call from the `{}` view",
+        command,
+        webview.label()
+    );
+    Err(format!("[cmd:{}] Not allowed from this page", command))
+}
+
+/// A free `ChatGPT-<timestamp>.<extension>` path in the download folder.
+fn export_path(app: &AppHandle, extension: &str) -> Option<PathBuf> {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or_default();
+    let name = format!("ChatGPT-{}.{}", timestamp, extension);
+    Some(download::unique_path(
+        download::download_dir(app)?.join(name),
+    ))
+}
+
+fn main_view(app: &AppHandle) -> Result<Webview, String> {
+    app.get_webview("main")
+        .ok_or_else(|| "[cmd:main_view] The main webview does not exist".to_string())
 }
@@ -1,95 +1,777 @@
-use log::error;
+use log::{error, warn};
+use once_cell::sync::OnceCell;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::{
-    collections::BTreeMap,
+    collections::{BTreeMap, HashMap},
     fs::{self, File},
     io::{Read, Write},
-    path::PathBuf,
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicUsize, Ordering},
 };
-use tauri::{AppHandle, Manager, Theme};
+use tauri::{AppHandle, Manager, Theme, Url};
 
+use crate::core::constant::{
+    ASK_HEIGHT, ASK_HEIGHT_RANGE, CONF_VERSION, DATA_DIR_ENV, DEFAULT_ALLOWED_HOSTS,
+    DEFAULT_AUTH_DOMAINS, DEFAULT_PROFILE, DEFAULT_SHORTCUTS, DEFAULT_START_URL,
+    DEFAULT_TITLE_TEMPLATE, DEFAULT_WINDOW_PRESETS, MAIN_VIEW_MIN_HEIGHT, OPACITY_RANGE,
+    SPLIT_RATIO, SPLIT_RATIO_RANGE, TITLEBAR_HEIGHT, TITLEBAR_HEIGHT_RANGE, WINDOW_HEIGHT,
+    WINDOW_MIN_HEIGHT, WINDOW_MIN_WIDTH, WINDOW_WIDTH, ZOOM_RANGE,
+};
+
+/// Where the titlebar view sits on Linux and Windows, macOS always keeps it on
+/// top for the traffic lights.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum TitlebarPosition {
+    #[default]
+    Top,
+    Bottom,
+}
+
+/// What happens to a finished download, see `download_actions`.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum DownloadAction {
+    Open,
+    /// Select it in the file manager.
+    Reveal,
+    None,
+}
+
+/// Whether ChatGPT's animations and transitions are turned off.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ReduceMotion {
+    #[default]
+    Off,
+    On,
+    /// Follows the OS reduced motion setting through `prefers-reduced-motion`.
+    Auto,
+}
+
+/// Most verbose level written to the log, see `logger::init`.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    Error,
+    Warn,
+    #[default]
+    Info,
+    Debug,
+    Trace,
+}
+
+/// Release feed `updater` checks, see `UPDATE_ENDPOINT_*`.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum UpdateChannel {
+    #[default]
+    Stable,
+    Beta,
+}
+
+/// Resolved once, the webviews can't move their storage while running.
+static ENV_DATA_DIR: OnceCell<Option<PathBuf>> = OnceCell::new();
+static DATA_DIR: OnceCell<Option<PathBuf>> = OnceCell::new();
+
+fn default_data_dir(app: &AppHandle) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    Ok(app.path().config_dir()?.join("com.nofwl.chatgpt"))
+}
+
+/// `CHATGPT_DATA_DIR`, which moves the config along with everything else.
+fn env_data_dir() -> Option<PathBuf> {
+    ENV_DATA_DIR
+        .get_or_init(|| {
+            let dir = std::env::var_os(DATA_DIR_ENV)?;
+            writable_dir(PathBuf::from(dir), DATA_DIR_ENV)
+        })
+        .clone()
+}
+
+/// `data_dir` from the config, else `CHATGPT_DATA_DIR`. `None` keeps the OS
+/// defaults.
+fn data_dir_override(app: &AppHandle) -> Option<PathBuf> {
+    if let Some(dir) = DATA_DIR.get() {
+        return dir.clone();
+    }
+    let dir = AppConf::load(app)
+        .ok()
+        .and_then(|conf| conf.data_dir)
+        .and_then(|dir| writable_dir(dir, "data_dir"))
+        .or_else(env_data_dir);
+    DATA_DIR.get_or_init(|| dir).clone()
+}
+
+/// Creates `dir` and checks a file can be written to it, so a read-only drive
+/// falls back to the OS default instead of failing later.
+fn writable_dir(dir: PathBuf, name: &str) -> Option<PathBuf> {
+    let probe = dir.join(".write-test");
+    let result = fs::create_dir_all(&dir)
+        .and_then(|_| fs::write(&probe, b""))
+        .and_then(|_| fs::remove_file(&probe));
+    match result {
+        Ok(()) => Some(dir),
+        Err(e) => {
+            warn!(
+                "[conf:data_dir] {} `{}` is not writable, using the OS default: {}",
+                name,
+                dir.display(),
+                e
+            );
+            None
+        }
+    }
+}
+
+/// `*.js` files directly in `dir`, in filename order.
+fn read_scripts(dir: &Path) -> Vec<String> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) => {
+            error!("[conf::load_scripts_dir] {}: {}", dir.display(), e);
+            return Vec::new();
+        }
+    };
+
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.is_file() && path.extension().is_some_and(|ext| ext == "js"))
+        .collect();
+    paths.sort();
+
+    paths
+        .into_iter()
+        .filter_map(|path| match fs::read_to_string(&path) {
+            Ok(script) => Some(script),
+            Err(e) => {
+                error!("[conf::load_scripts_dir] {}: {}", path.display(), e);
+                None
+            }
+        })
+        .collect()
+}
+
+/// A logical window size, as used by `window_presets`.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct WindowSize {
+    pub width: f64,
+    pub height: f64,
+}
+
+/// Missing fields take their defaults, so older files keep loading as fields
+/// are added.
 #[derive(Serialize, Deserialize, Debug)]
+#[serde(default)]
 pub struct AppConf {
+    /// Layout of the file, bumped with `CONF_VERSION` when `migrate` learns a new step.
+    pub version: u32,
     pub theme: String,
     pub stay_on_top: bool,
+    /// Keep the window on every workspace, Linux and macOS only.
+    pub visible_on_all_workspaces: bool,
     pub ask_mode: bool,
     pub mac_titlebar_hidden: bool,
+    pub window_width: f64,
+    pub window_height: f64,
+    pub window_x: Option<f64>,
+    pub window_y: Option<f64>,
+    /// Named sizes for `resize_preset`.
+    pub window_presets: HashMap<String, WindowSize>,
+    pub min_width: f64,
+    pub min_height: f64,
+    /// Only `https://chatgpt.com` is allowed in `capabilities/desktop.json`, any other
+    /// origin gets a runtime capability so the injected scripts can emit events.
+    pub start_url: String,
+    /// Use the custom titlebar view; when off the native titlebar is shown instead.
+    pub custom_titlebar: bool,
+    pub titlebar_height: f64,
+    pub titlebar_position: TitlebarPosition,
+    /// Skip the webviews' `auto_resize` and size them only from the window's
+    /// resize handler. Avoids both fighting over the size, which flickers on
+    /// some Linux window managers, at the cost of views lagging slightly behind
+    /// a live resize.
+    pub manual_layout_only: bool,
+    /// Show `reference_url` in a pane right of the main view, see `set_reference_url`.
+    pub split_view: bool,
+    pub reference_url: Option<String>,
+    /// Share of the width left to the main view while `split_view` is on.
+    pub split_ratio: f64,
+    /// Keeps scripts, profiles and the webview data under one folder, read on
+    /// startup. The config itself stays in the OS config dir so it can be found,
+    /// set `CHATGPT_DATA_DIR` to move it as well.
+    pub data_dir: Option<PathBuf>,
+    pub download_dir: Option<PathBuf>,
+    pub open_downloads_on_finish: bool,
+    /// Per lowercase extension, e.g. `{ "png": "open", "zip": "reveal" }`. Other
+    /// files follow `open_downloads_on_finish`.
+    pub download_actions: HashMap<String, DownloadAction>,
+    /// Files downloaded by the app kept in the download folder before the oldest
+    /// are deleted, 0 keeps them all. Only this session's downloads are counted.
+    pub max_downloads: usize,
+    pub enable_tray: bool,
+    pub close_to_tray: bool,
+    /// `close_to_tray` for a single OS, keyed by `macos`, `windows` or `linux`.
+    /// The entry for the running OS takes precedence over `close_to_tray`.
+    pub close_to_tray_platforms: HashMap<String, bool>,
+    /// Ask in the titlebar before `quit` exits the app.
+    pub confirm_quit: bool,
+    /// Accelerators by action, overriding `DEFAULT_SHORTCUTS`. An empty string
+    /// leaves the action unbound, see `shortcuts`.
+    pub shortcuts: HashMap<String, String>,
+    /// Focus the prompt whenever the core window is shown or focused, e.g. by the
+    /// `toggle_window` shortcut. With ask mode on the ask strip keeps the focus.
+    pub focus_composer_on_show: bool,
+    /// Takes effect on the next layout pass, i.e. when the window is resized
+    /// or ask mode is toggled.
+    pub ask_height: f64,
+    /// Build the ask strip with a transparent background, floating over the
+    /// bottom of the main view. Unsupported on macOS, which lacks the private API.
+    pub transparent_ask: bool,
+    /// Main view zoom keyed by host, unseen hosts use 1.0.
+    pub host_zoom: HashMap<String, f64>,
+    /// Active profile, each one keeps its own cookies and sessions.
+    pub profile: String,
+    /// Keep nothing on disk for the main view, the active profile is ignored.
+    pub incognito: bool,
+    pub opacity: f64,
+    /// Ask DWM for rounded window corners, applied on startup. Only Windows 11
+    /// supports it, elsewhere it does nothing.
+    pub rounded_corners: bool,
+    /// `#rrggbb` painted behind the webviews before pages draw, `None` follows
+    /// the theme.
+    pub background_color: Option<String>,
+    /// macOS only: an `NSVisualEffectView` material such as `titlebar`, `sidebar`,
+    /// `header_view` or `under_window_background`, see `platform::set_vibrancy`.
+    pub vibrancy: Option<String>,
+    /// `http://`, `https://` or `socks5://` proxy for the main webview.
+    pub proxy_url: Option<String>,
+    /// User agent for the main webview; an empty string uses the platform default.
+    pub user_agent: String,
+    /// Reopen `last_url` on startup instead of `start_url`.
+    pub restore_last_url: bool,
+    /// Check `update_channel` for a newer version on startup.
+    pub auto_update: bool,
+    pub update_channel: UpdateChannel,
+    /// Show the onboarding overlay in the titlebar, cleared once it has been shown.
+    pub first_run: bool,
+    pub last_url: Option<String>,
+    /// Seconds between background retries while the offline page is shown, 0 to
+    /// only retry from its button.
+    pub retry_interval_secs: u64,
+    /// Go back to `start_url` after this many minutes without focus or input,
+    /// 0 to never.
+    pub idle_reload_minutes: u64,
+    /// Show a native notification when a response finishes while the window is
+    /// in the background. Applied when the main view is created.
+    pub notify_on_complete: bool,
+    /// Conversations kept for `next_conversation`/`prev_conversation`.
+    pub conversation_history_size: usize,
+    /// Window title while a conversation is open, `{chat}` is replaced with its
+    /// name. Other pages use the plain app name.
+    pub title_template: String,
+    /// Route opened by `new_chat`, relative to `start_url`.
+    pub new_chat_path: String,
+    /// Model picked in ChatGPT's model switcher after every page load, see
+    /// `set_model`. `None` leaves the page's choice alone.
+    pub default_model: Option<String>,
+    /// Hosts besides the start URL's that stay in the app instead of opening in
+    /// the system browser, subdomains included.
+    pub allowed_hosts: Vec<String>,
+    /// Login hosts whose popups open in the main view, which has no popup support.
+    /// They are also kept in the app like `allowed_hosts`.
+    pub auth_domains: Vec<String>,
+    pub spellcheck: bool,
+    /// Last sidebar state picked with `toggle_sidebar`, re-applied on every page load.
+    pub sidebar_collapsed: bool,
+    /// Silence the main view's audio and video, reapplied on every page load.
+    pub muted: bool,
+    pub reduce_motion: ReduceMotion,
+    /// Subfolder of the scripts folder whose scripts are injected after the shared
+    /// ones, see `apply_script_preset`.
+    pub script_preset: Option<String>,
+    /// Re-apply scripts and `custom.css` to the main view when they change on disk.
+    pub watch_scripts: bool,
+    /// Recreate the main view at its last URL when its content process crashes.
+    pub auto_recover_webview: bool,
+    /// Read on startup, `debug` or `trace` help when attaching the log to a report.
+    pub log_level: LogLevel,
+    /// Allow DevTools in release builds, debug builds always have them.
+    pub enable_devtools: bool,
 }
 
 impl AppConf {
     pub fn new() -> Self {
         Self {
+            version: CONF_VERSION,
             theme: "system".to_string(),
             stay_on_top: false,
+            visible_on_all_workspaces: false,
             ask_mode: false,
             #[cfg(target_os = "macos")]
             mac_titlebar_hidden: true,
             #[cfg(not(target_os = "macos"))]
             mac_titlebar_hidden: false,
+            window_width: WINDOW_WIDTH,
+            window_height: WINDOW_HEIGHT,
+            window_x: None,
+            window_y: None,
+            window_presets: DEFAULT_WINDOW_PRESETS
+                .iter()
+                .map(|&(name, width, height)| (name.to_string(), WindowSize { width, height }))
+                .collect(),
+            min_width: WINDOW_MIN_WIDTH,
+            min_height: WINDOW_MIN_HEIGHT,
+            start_url: DEFAULT_START_URL.to_string(),
+            custom_titlebar: true,
+            titlebar_height: TITLEBAR_HEIGHT,
+            titlebar_position: TitlebarPosition::Top,
+            manual_layout_only: false,
+            split_view: false,
+            reference_url: None,
+            split_ratio: SPLIT_RATIO,
+            data_dir: None,
+            download_dir: None,
+            open_downloads_on_finish: false,
+            download_actions: HashMap::new(),
+            max_downloads: 0,
+            enable_tray: true,
+            close_to_tray: false,
+            close_to_tray_platforms: HashMap::new(),
+            confirm_quit: false,
+            shortcuts: HashMap::new(),
+            focus_composer_on_show: false,
+            ask_height: ASK_HEIGHT,
+            transparent_ask: false,
+            host_zoom: HashMap::new(),
+            profile: DEFAULT_PROFILE.to_string(),
+            incognito: false,
+            opacity: 1.0,
+            rounded_corners: true,
+            background_color: None,
+            vibrancy: None,
+            proxy_url: None,
+            user_agent: "".to_string(),
+            restore_last_url: false,
+            auto_update: true,
+            update_channel: UpdateChannel::Stable,
+            first_run: true,
+            last_url: None,
+            retry_interval_secs: 30,
+            idle_reload_minutes: 0,
+            notify_on_complete: false,
+            conversation_history_size: 20,
+            title_template: DEFAULT_TITLE_TEMPLATE.to_string(),
+            new_chat_path: "/".to_string(),
+            default_model: None,
+            allowed_hosts: DEFAULT_ALLOWED_HOSTS
+                .iter()
+                .map(|host| host.to_string())
+                .collect(),
+            auth_domains: DEFAULT_AUTH_DOMAINS
+                .iter()
+                .map(|host| host.to_string())
+                .collect(),
+            spellcheck: true,
+            sidebar_collapsed: false,
+            muted: false,
+            reduce_motion: ReduceMotion::Off,
+            script_preset: None,
+            watch_scripts: false,
+            auto_recover_webview: true,
+            log_level: LogLevel::Info,
+            enable_devtools: false,
         }
     }
 
     pub fn get_conf_path(app: &AppHandle) -> Result<PathBuf, Box<dyn std::error::Error>> {
-        let config_dir = app
-            .path()
-            .config_dir()?
-            .join("com.nofwl.chatgpt")
-            .join("config.json");
-        Ok(config_dir)
+        let conf_dir = match env_data_dir() {
+            Some(dir) => dir,
+            None => default_data_dir(app)?,
+        };
+        Ok(conf_dir.join("config.json"))
+    }
+
+    /// Folder for scripts and webview data, see `data_dir`.
+    pub fn get_data_dir(app: &AppHandle) -> Result<PathBuf, Box<dyn std::error::Error>> {
+        match data_dir_override(app) {
+            Some(dir) => Ok(dir),
+            None => default_data_dir(app),
+        }
     }
 
+    /// Webview data directory of `profile`, `None` for the default profile which
+    /// keeps the webview's own storage location unless `data_dir` moves it.
+    pub fn get_profile_path(
+        app: &AppHandle,
+        profile: &str,
+    ) -> Result<Option<PathBuf>, Box<dyn std::error::Error>> {
+        if profile == DEFAULT_PROFILE {
+            return Ok(data_dir_override(app).map(|dir| dir.join("webview")));
+        }
+        let profile_dir = Self::get_data_dir(app)?.join("profiles").join(profile);
+        Ok(Some(profile_dir))
+    }
+
+    /// Returns the scripts folder, creating it if it doesn't exist yet.
     pub fn get_scripts_path(app: &AppHandle) -> Result<PathBuf, Box<dyn std::error::Error>> {
-        let scripts_dir = app
-            .path()
-            .config_dir()?
-            .join("com.nofwl.chatgpt")
-            .join("scripts");
-        Ok(scripts_dir)
+        Ok(scripts_dir(&Self::get_data_dir(app)?)?)
     }
 
-    pub fn load_script(app: &AppHandle, filename: &str) -> String {
-        let script_file = Self::get_scripts_path(app).unwrap().join(filename);
-        fs::read_to_string(script_file).unwrap_or_else(|_| "".to_string())
+    /// What to do with `path` once downloaded, from `download_actions` by its
+    /// extension, else `open_downloads_on_finish`.
+    pub fn download_action(&self, path: &Path) -> DownloadAction {
+        let listed = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .and_then(|ext| self.download_actions.get(&ext.to_lowercase()));
+        match listed {
+            Some(action) => *action,
+            None if self.open_downloads_on_finish => DownloadAction::Open,
+            None => DownloadAction::None,
+        }
+    }
+
+    /// Returns the configured download directory, falling back to the OS default
+    /// if it is missing or not writable.
+    pub fn get_download_dir(app: &AppHandle) -> Result<PathBuf, Box<dyn std::error::Error>> {
+        if let Some(dir) = Self::load(app)?.download_dir {
+            let writable = fs::metadata(&dir)
+                .map(|meta| meta.is_dir() && !meta.permissions().readonly())
+                .unwrap_or(false);
+            if writable {
+                return Ok(dir);
+            }
+            warn!(
+                "[conf::download_dir] `{}` is missing or not writable, using the OS default",
+                dir.display()
+            );
+        }
+        Ok(app.path().download_dir()?)
+    }
+
+    /// Names of the preset subfolders of the scripts folder, sorted.
+    pub fn list_script_presets(app: &AppHandle) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+        let mut presets: Vec<String> = fs::read_dir(Self::get_scripts_path(app)?)?
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.is_dir())
+            .filter_map(|path| Some(path.file_name()?.to_str()?.to_string()))
+            .filter(|name| !name.starts_with('.'))
+            .collect();
+        presets.sort();
+        Ok(presets)
+    }
+
+    /// Reads every `*.js` file in the scripts folder in filename order, then the
+    /// ones in the `preset` subfolder, skipping (and logging) the ones that can't
+    /// be read.
+    pub fn load_scripts_dir(app: &AppHandle, preset: Option<&str>) -> Vec<String> {
+        let scripts_dir = match Self::get_scripts_path(app) {
+            Ok(dir) => dir,
+            Err(e) => {
+                error!("[conf::load_scripts_dir] {}", e);
+                return Vec::new();
+            }
+        };
+
+        let mut scripts = read_scripts(&scripts_dir);
+        if let Some(preset) = preset {
+            let preset_dir = scripts_dir.join(preset);
+            if preset_dir.is_dir() {
+                scripts.extend(read_scripts(&preset_dir));
+            } else {
+                warn!(
+                    "[conf::load_scripts_dir] Script preset `{}` does not exist",
+                    preset
+                );
+            }
+        }
+        scripts
+    }
+
+    /// Wraps a stylesheet from the scripts folder in an initialization script
+    /// that injects it as a `<style>` tag, `None` if the file doesn't exist.
+    pub fn load_style(app: &AppHandle, filename: &str) -> Option<String> {
+        let style_file = Self::get_scripts_path(app).ok()?.join(filename);
+        let css = fs::read_to_string(style_file).ok()?;
+        // Reuses the tag for the same file, so the script can be re-evaluated in place
+        Some(format!(
+            r#"(function () {{
+  const file = {};
+  const inject = () => {{
+    let style = Array.from(document.querySelectorAll('style[data-file]'))
+      .find((el) => el.dataset.file === file);
+    if (!style) {{
+      style = document.createElement('style');
+      style.dataset.file = file;
+      document.head.appendChild(style);
+    }}
+    style.textContent = {};
+  }};
+  if (document.head) inject();
+  else document.addEventListener('DOMContentLoaded', inject);
+}})();"#,
+            serde_json::to_string(filename).ok()?,
+            serde_json::to_string(&css).ok()?
+        ))
     }
 
     pub fn load(app: &AppHandle) -> Result<Self, Box<dyn std::error::Error>> {
-        let path = Self::get_conf_path(app)?;
+        Self::load_path(&Self::get_conf_path(app)?)
+    }
 
+    /// `load` from the config file at `path`.
+    fn load_path(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
         if !path.exists() {
             let config = Self::new();
-            config.save(app)?;
+            config.save_path(path)?;
             return Ok(config);
         }
 
-        let mut file = File::open(path)?;
-        let mut contents = String::new();
-        file.read_to_string(&mut contents)?;
-        let config: Result<AppConf, _> = serde_json::from_str(&contents);
+        let mut json = match read_json(path) {
+            Ok(json) => json,
+            Err(e) => {
+                error!(
+                    "[conf::load] {} is unreadable, trying the backup: {}",
+                    path.display(),
+                    e
+                );
+                match read_json(&backup_path(path)) {
+                    Ok(json) => {
+                        // Restored first so the next save doesn't back up the broken file
+                        fs::copy(backup_path(path), path)?;
+                        json
+                    }
+                    Err(e) => {
+                        error!("[conf::load] No usable backup, using defaults: {}", e);
+                        let config = Self::new();
+                        config.save_path(path)?;
+                        return Ok(config);
+                    }
+                }
+            }
+        };
+
+        // `save` keeps the pre-migration file as the backup, for older builds
+        let migrated = migrate(&mut json);
+        let config: Result<AppConf, _> = serde_json::from_value(json.clone());
 
         // Handle conditional fields and fallback to defaults if necessary
         if let Err(e) = &config {
             error!("[conf::load] {}", e);
             let mut default_config = Self::new();
-            default_config = default_config.amend(serde_json::from_str(&contents)?)?;
-            default_config.save(app)?;
+            default_config = default_config.amend(json)?;
+            default_config.sanitize();
+            default_config.save_path(path)?;
             return Ok(default_config);
         }
 
-        Ok(config?)
+        let mut config = config?;
+        if config.sanitize() || migrated {
+            config.save_path(path)?;
+        }
+        Ok(config)
+    }
+
+    /// Replaces invalid values with their defaults, returning whether anything changed.
+    fn sanitize(&mut self) -> bool {
+        let mut changed = false;
+
+        let start_url_valid = Url::parse(&self.start_url)
+            .map(|url| matches!(url.scheme(), "http" | "https"))
+            .unwrap_or(false);
+        if !start_url_valid {
+            warn!(
+                "[conf::sanitize] Invalid start_url `{}`, falling back to {}",
+                self.start_url, DEFAULT_START_URL
+            );
+            self.start_url = DEFAULT_START_URL.to_string();
+            changed = true;
+        }
+
+        if let Some(proxy_url) = &self.proxy_url {
+            let proxy_url_valid = Url::parse(proxy_url)
+                .map(|url| matches!(url.scheme(), "http" | "https" | "socks5") && url.has_host())
+                .unwrap_or(false);
+            if !proxy_url_valid {
+                warn!(
+                    "[conf::sanitize] Invalid proxy_url `{}`, falling back to no proxy",
+                    proxy_url
+                );
+                self.proxy_url = None;
+                changed = true;
+            }
+        }
+
+        if !is_valid_profile_name(&self.profile) {
+            warn!(
+                "[conf::sanitize] Invalid profile `{}`, falling back to {}",
+                self.profile, DEFAULT_PROFILE
+            );
+            self.profile = DEFAULT_PROFILE.to_string();
+            changed = true;
+        }
+
+        changed |= clamp_field(
+            "titlebar_height",
+            &mut self.titlebar_height,
+            TITLEBAR_HEIGHT_RANGE,
+        );
+        changed |= clamp_field("ask_height", &mut self.ask_height, ASK_HEIGHT_RANGE);
+        for (host, zoom) in self.host_zoom.iter_mut() {
+            changed |= clamp_field(&format!("host_zoom.{}", host), zoom, ZOOM_RANGE);
+        }
+        changed |= clamp_field("opacity", &mut self.opacity, OPACITY_RANGE);
+        changed |= clamp_field("split_ratio", &mut self.split_ratio, SPLIT_RATIO_RANGE);
+
+        changed
+    }
+
+    /// Height of the ask strip in logical pixels, zero when ask mode is disabled.
+    pub fn ask_mode_height(&self) -> f64 {
+        if self.ask_mode {
+            self.ask_height
+        } else {
+            0.0
+        }
+    }
+
+    /// Whether the ask strip floats over the main view instead of sitting below it.
+    pub fn ask_floats(&self) -> bool {
+        self.transparent_ask && cfg!(not(target_os = "macos"))
+    }
+
+    /// The accelerator bound to `action`, empty when it is unbound or unknown.
+    pub fn shortcut(&self, action: &str) -> &str {
+        if let Some(accelerator) = self.shortcuts.get(action) {
+            return accelerator;
+        }
+        DEFAULT_SHORTCUTS
+            .iter()
+            .find(|(name, _)| *name == action)
+            .map_or("", |(_, accelerator)| accelerator)
+    }
+
+    /// `split_ratio` while the reference pane is shown, `None` otherwise.
+    pub fn split_mode_ratio(&self) -> Option<f64> {
+        (self.split_view && self.reference_url().is_some()).then_some(self.split_ratio)
+    }
+
+    /// `reference_url` if it is a web page.
+    pub fn reference_url(&self) -> Option<Url> {
+        let url = Url::parse(self.reference_url.as_deref()?).ok()?;
+        matches!(url.scheme(), "http" | "https").then_some(url)
+    }
+
+    /// Whether the close button hides the window instead of quitting: the
+    /// running OS's `close_to_tray_platforms` entry, else `close_to_tray`.
+    /// Without a tray there would be no way back, so the window always closes.
+    pub fn closes_to_tray(&self) -> bool {
+        self.enable_tray
+            && self
+                .close_to_tray_platforms
+                .get(std::env::consts::OS)
+                .copied()
+                .unwrap_or(self.close_to_tray)
+    }
+
+    /// Zoom saved for the host of `url`.
+    pub fn zoom_for(&self, url: &Url) -> f64 {
+        url.host_str()
+            .and_then(|host| self.host_zoom.get(host))
+            .copied()
+            .unwrap_or(1.0)
+    }
+
+    /// Minimum logical window size, with the height raised so the titlebar,
+    /// ask strip and some of the main view always fit.
+    pub fn min_window_size(&self) -> (f64, f64) {
+        let floor = self.titlebar_mode_height() + self.ask_height + MAIN_VIEW_MIN_HEIGHT;
+        (self.min_width, self.min_height.max(floor))
+    }
+
+    /// Height of the custom titlebar in logical pixels, zero when it is disabled.
+    pub fn titlebar_mode_height(&self) -> f64 {
+        if self.custom_titlebar {
+            self.titlebar_height
+        } else {
+            0.0
+        }
+    }
+
+    pub fn start_url(&self) -> Url {
+        Url::parse(&self.start_url).unwrap_or_else(|_| DEFAULT_START_URL.parse().unwrap())
+    }
+
+    /// The URL the main view opens with: `last_url` when restoring is enabled and
+    /// it shares an origin with `start_url`, otherwise `start_url`.
+    pub fn initial_url(&self) -> Url {
+        let start_url = self.start_url();
+        if !self.restore_last_url {
+            return start_url;
+        }
+        self.last_url
+            .as_deref()
+            .and_then(|url| Url::parse(url).ok())
+            .filter(|url| url.origin() == start_url.origin())
+            .unwrap_or(start_url)
+    }
+
+    pub fn proxy_url(&self) -> Option<Url> {
+        self.proxy_url
+            .as_deref()
+            .and_then(|url| Url::parse(url).ok())
+    }
+
+    /// Reads settings written by `export_settings`, migrated like `load` does.
+    /// Unlike `load` nothing is filled in from the defaults, a file that doesn't
+    /// match the schema is rejected with what's wrong with it.
+    pub fn from_file(path: &Path) -> Result<Self, String> {
+        let mut json = read_json(path)
+            .map_err(|e| format!("{} is not a settings file: {}", path.display(), e))?;
+        if !json.is_object() {
+            return Err(format!("{} is not a settings object", path.display()));
+        }
+        let version = json.get("version").and_then(Value::as_u64).unwrap_or(0);
+        if version > CONF_VERSION as u64 {
+            return Err(format!(
+                "Settings are from a newer version (v{}, this build reads up to v{})",
+                version, CONF_VERSION
+            ));
+        }
+        migrate(&mut json);
+        let mut config: AppConf =
+            serde_json::from_value(json).map_err(|e| format!("Invalid settings: {}", e))?;
+        config.sanitize();
+        Ok(config)
     }
 
     pub fn save(&self, app: &AppHandle) -> Result<(), Box<dyn std::error::Error>> {
-        let path = Self::get_conf_path(app)?;
+        self.save_path(&Self::get_conf_path(app)?)
+    }
 
+    /// `save` to the config file at `path`.
+    fn save_path(&self, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
         if let Some(dir) = path.parent() {
             fs::create_dir_all(dir)?;
         }
 
-        let mut file = File::create(path)?;
         let contents = serde_json::to_string_pretty(self)?;
-        // dbg!(&contents);
+        // Written aside and renamed over the file, so a crash mid-write can't
+        // truncate it. Concurrent saves each get their own temp file.
+        static SAVE_ID: AtomicUsize = AtomicUsize::new(0);
+        let tmp_path = path.with_extension(format!(
+            "json.{}.tmp",
+            SAVE_ID.fetch_add(1, Ordering::Relaxed)
+        ));
+        let mut file = File::create(&tmp_path)?;
         file.write_all(contents.as_bytes())?;
+        file.sync_all()?;
+
+        if path.exists() {
+            fs::copy(path, backup_path(path))?;
+        }
+        fs::rename(&tmp_path, path)?;
         Ok(())
     }
 
@@ -109,16 +791,415 @@ impl AppConf {
         })
     }
 
+    /// Like `amend`, for a patch from outside. Unknown fields, values of the wrong
+    /// type and values `sanitize` would replace are refused, naming the field.
+    pub fn amend_checked(self, patch: Value) -> Result<Self, String> {
+        let Value::Object(patch) = patch else {
+            return Err("The settings must be a JSON object".into());
+        };
+        let current = serde_json::to_value(&self).map_err(|e| e.to_string())?;
+        for (key, value) in &patch {
+            if key == "version" {
+                return Err("`version` is managed by the app".into());
+            }
+            if current.get(key).is_none() {
+                return Err(format!("Unknown field `{}`", key));
+            }
+            // One field at a time, serde's errors don't say which one failed
+            let mut single = current.clone();
+            single[key] = value.clone();
+            if let Err(e) = serde_json::from_value::<AppConf>(single) {
+                return Err(format!("Invalid `{}`: {}", key, e));
+            }
+        }
+
+        let mut config = self
+            .amend(Value::Object(patch.clone()))
+            .map_err(|e| e.to_string())?;
+        let before = serde_json::to_value(&config).map_err(|e| e.to_string())?;
+        if config.sanitize() {
+            let after = serde_json::to_value(&config).map_err(|e| e.to_string())?;
+            if let Some(key) = patch.keys().find(|key| before[key] != after[key]) {
+                return Err(format!(
+                    "Invalid `{}` {}, the closest valid value is {}",
+                    key, before[key], after[key]
+                ));
+            }
+        }
+        Ok(config)
+    }
+
+    /// The window background as RGB, white when `background_color` doesn't parse.
+    pub fn background_color(&self, theme: Theme) -> (u8, u8, u8) {
+        match &self.background_color {
+            Some(color) => parse_hex_color(color).unwrap_or_else(|| {
+                warn!(
+                    "[conf::background_color] Invalid color `{}`, falling back to white",
+                    color
+                );
+                (0xff, 0xff, 0xff)
+            }),
+            None if theme == Theme::Dark => (0x21, 0x21, 0x21),
+            None => (0xff, 0xff, 0xff),
+        }
+    }
+
     pub fn get_theme(app: &AppHandle) -> Theme {
         let theme = Self::load(app).unwrap().theme;
-        match theme.as_str() {
-            "system" => match dark_light::detect() {
-                dark_light::Mode::Dark => Theme::Dark,
-                dark_light::Mode::Light => Theme::Light,
-                dark_light::Mode::Default => Theme::Light,
-            },
-            "dark" => Theme::Dark,
-            _ => Theme::Light,
+        resolve_theme(&theme, dark_light::detect)
+    }
+}
+
+/// Maps the `theme` setting to a window theme, `system` asks for the OS mode.
+fn resolve_theme(theme: &str, system: impl FnOnce() -> dark_light::Mode) -> Theme {
+    match theme {
+        "system" => match system() {
+            dark_light::Mode::Dark => Theme::Dark,
+            dark_light::Mode::Light => Theme::Light,
+            dark_light::Mode::Default => Theme::Light,
+        },
+        "dark" => Theme::Dark,
+        _ => Theme::Light,
+    }
+}
+
+impl Default for AppConf {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The `scripts` folder of `data_dir`, created if it doesn't exist yet.
+fn scripts_dir(data_dir: &Path) -> std::io::Result<PathBuf> {
+    let scripts_dir = data_dir.join("scripts");
+    fs::create_dir_all(&scripts_dir)?;
+    Ok(scripts_dir)
+}
+
+/// The previous config, kept by every `save`.
+fn backup_path(conf_path: &Path) -> PathBuf {
+    conf_path.with_file_name("conf.bak")
+}
+
+fn read_json(path: &Path) -> Result<Value, Box<dyn std::error::Error>> {
+    let mut contents = String::new();
+    File::open(path)?.read_to_string(&mut contents)?;
+    Ok(serde_json::from_str(&contents)?)
+}
+
+/// Upgrades a config file written by an older build to `CONF_VERSION` in place,
+/// returning whether anything changed. Files from before versioning are v0.
+fn migrate(json: &mut Value) -> bool {
+    let Some(conf) = json.as_object_mut() else {
+        return false;
+    };
+    let version = conf.get("version").and_then(Value::as_u64).unwrap_or(0);
+    if version >= CONF_VERSION as u64 {
+        return false;
+    }
+
+    if version < 1 {
+        // v1 replaced the global `zoom` with `host_zoom`, the old value is kept
+        // for the start URL's host
+        if let Some(zoom) = conf.remove("zoom").and_then(|zoom| zoom.as_f64()) {
+            let host = conf
+                .get("start_url")
+                .and_then(Value::as_str)
+                .unwrap_or(DEFAULT_START_URL)
+                .parse::<Url>()
+                .ok()
+                .and_then(|url| url.host_str().map(str::to_string));
+            if let Some(host) = host.filter(|_| zoom != 1.0) {
+                let host_zoom = conf
+                    .entry("host_zoom")
+                    .or_insert_with(|| Value::Object(Default::default()));
+                if let Some(host_zoom) = host_zoom.as_object_mut() {
+                    host_zoom.entry(host).or_insert(zoom.into());
+                }
+            }
+        }
+    }
+
+    if version < 2 {
+        // v2 gathered the `<action>_shortcut` fields into `shortcuts`
+        let fields: Vec<String> = conf
+            .keys()
+            .filter(|key| key.ends_with("_shortcut"))
+            .cloned()
+            .collect();
+        let mut shortcuts = serde_json::Map::new();
+        for field in fields {
+            let Some(Value::String(accelerator)) = conf.remove(&field) else {
+                continue;
+            };
+            let action = match field.trim_end_matches("_shortcut") {
+                "toggle" => "toggle_window",
+                action => action,
+            };
+            shortcuts.insert(action.to_string(), accelerator.into());
+        }
+        if !shortcuts.is_empty() {
+            conf.insert("shortcuts".into(), Value::Object(shortcuts));
+        }
+    }
+
+    warn!(
+        "[conf::migrate] Upgraded config from v{} to v{}",
+        version, CONF_VERSION
+    );
+    conf.insert("version".into(), CONF_VERSION.into());
+    true
+}
+
+/// Profile names become directory names, so only a safe subset is allowed.
+pub fn is_valid_profile_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.len() <= 64
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// Parses `#rrggbb` or `#rgb`.
+fn parse_hex_color(color: &str) -> Option<(u8, u8, u8)> {
+    let hex = color.strip_prefix('#')?;
+    if !hex.is_ascii() {
+        return None;
+    }
+    let channel = |digits: &str| u8::from_str_radix(digits, 16).ok();
+    match hex.len() {
+        6 => Some((
+            channel(&hex[0..2])?,
+            channel(&hex[2..4])?,
+            channel(&hex[4..6])?,
+        )),
+        3 => Some((
+            channel(&hex[0..1].repeat(2))?,
+            channel(&hex[1..2].repeat(2))?,
+            channel(&hex[2..3].repeat(2))?,
+        )),
+        _ => None,
+    }
+}
+
+/// Clamps `value` into `range`, returning whether it was out of bounds.
+fn clamp_field(name: &str, value: &mut f64, (min, max): (f64, f64)) -> bool {
+    if (min..=max).contains(value) {
+        return false;
+    }
+    warn!(
+        "[conf::sanitize] {} {} out of range, clamping to {}-{}",
+        name, value, min, max
+    );
+    *value = value.clamp(min, max);
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    /// A fresh directory under the system temp dir, removed on drop.
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new(name: &str) -> Self {
+            let dir =
+                std::env::temp_dir().join(format!("chatgpt-conf-{}-{}", name, std::process::id()));
+            let _ = fs::remove_dir_all(&dir);
+            fs::create_dir_all(&dir).unwrap();
+            Self(dir)
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    #[test]
+    fn load_recovers_from_the_backup() {
+        let dir = TempDir::new("backup");
+        let path = dir.0.join("config.json");
+        let first = AppConf {
+            opacity: 0.5,
+            ..AppConf::new()
+        };
+        first.save_path(&path).unwrap();
+        // The second save backs up the first
+        AppConf {
+            opacity: 0.7,
+            ..AppConf::new()
         }
+        .save_path(&path)
+        .unwrap();
+        fs::write(&path, "{ \"opacity\": 0.7,").unwrap();
+
+        let conf = AppConf::load_path(&path).unwrap();
+        assert_eq!(conf.opacity, 0.5);
+        assert_eq!(read_json(&path).unwrap()["opacity"], 0.5);
+    }
+
+    #[test]
+    fn load_falls_back_to_defaults_without_a_backup() {
+        let dir = TempDir::new("no-backup");
+        let path = dir.0.join("config.json");
+        fs::write(&path, "not json").unwrap();
+
+        let conf = AppConf::load_path(&path).unwrap();
+        assert_eq!(conf.opacity, AppConf::new().opacity);
+        assert!(read_json(&path).is_ok());
+    }
+
+    #[test]
+    fn scripts_dir_is_created_and_seeded() {
+        let dir = TempDir::new("scripts");
+        let scripts = scripts_dir(&dir.0.join("data")).unwrap();
+        assert!(scripts.is_dir());
+        // Calling it again on the existing folder is fine
+        assert_eq!(scripts_dir(&dir.0.join("data")).unwrap(), scripts);
+
+        crate::core::template::Template::new(&scripts);
+        assert!(scripts.join("ask.js").is_file());
+        assert!(scripts.join("custom.css").is_file());
+    }
+
+    #[test]
+    fn migrate_upgrades_a_v0_config() {
+        let mut json = json!({
+            "start_url": "https://chat.openai.com",
+            "zoom": 1.5,
+            "toggle_shortcut": "Alt+Space",
+            "ask_shortcut": "CmdOrCtrl+K",
+        });
+
+        assert!(migrate(&mut json));
+        assert_eq!(
+            json,
+            json!({
+                "version": CONF_VERSION,
+                "start_url": "https://chat.openai.com",
+                "host_zoom": { "chat.openai.com": 1.5 },
+                "shortcuts": {
+                    "toggle_window": "Alt+Space",
+                    "ask": "CmdOrCtrl+K",
+                },
+            })
+        );
+    }
+
+    #[test]
+    fn migrate_drops_the_default_zoom() {
+        let mut json = json!({ "zoom": 1.0 });
+
+        assert!(migrate(&mut json));
+        assert_eq!(json, json!({ "version": CONF_VERSION }));
+    }
+
+    #[test]
+    fn migrate_leaves_a_current_config_alone() {
+        let mut json = json!({ "version": CONF_VERSION, "zoom": 1.5 });
+
+        assert!(!migrate(&mut json));
+        assert_eq!(json, json!({ "version": CONF_VERSION, "zoom": 1.5 }));
+    }
+
+    #[test]
+    fn sanitize_clamps_out_of_range_values() {
+        let mut conf = AppConf {
+            titlebar_height: 0.0,
+            ask_height: 1000.0,
+            host_zoom: HashMap::from([("chatgpt.com".to_string(), 10.0)]),
+            opacity: -1.0,
+            split_ratio: 0.9,
+            ..AppConf::new()
+        };
+
+        assert!(conf.sanitize());
+        assert_eq!(conf.titlebar_height, TITLEBAR_HEIGHT_RANGE.0);
+        assert_eq!(conf.ask_height, ASK_HEIGHT_RANGE.1);
+        assert_eq!(conf.host_zoom["chatgpt.com"], ZOOM_RANGE.1);
+        assert_eq!(conf.opacity, OPACITY_RANGE.0);
+        assert_eq!(conf.split_ratio, SPLIT_RATIO_RANGE.1);
+        assert!(!conf.sanitize());
+    }
+
+    #[test]
+    fn sanitize_replaces_invalid_urls_and_profile() {
+        let mut conf = AppConf {
+            start_url: "file:///etc/passwd".to_string(),
+            proxy_url: Some("ftp://proxy".to_string()),
+            profile: "../work".to_string(),
+            ..AppConf::new()
+        };
+
+        assert!(conf.sanitize());
+        assert_eq!(conf.start_url, DEFAULT_START_URL);
+        assert_eq!(conf.proxy_url, None);
+        assert_eq!(conf.profile, DEFAULT_PROFILE);
+    }
+
+    #[test]
+    fn sanitize_keeps_the_defaults() {
+        assert!(!AppConf::new().sanitize());
+    }
+
+    #[test]
+    fn closes_to_tray_prefers_the_platform_entry() {
+        let os = std::env::consts::OS.to_string();
+        let conf = |enable_tray, close_to_tray, platform: Option<bool>| AppConf {
+            enable_tray,
+            close_to_tray,
+            close_to_tray_platforms: platform
+                .map(|value| HashMap::from([(os.clone(), value)]))
+                .unwrap_or_default(),
+            ..AppConf::new()
+        };
+
+        assert!(conf(true, true, None).closes_to_tray());
+        assert!(!conf(true, false, None).closes_to_tray());
+        assert!(conf(true, false, Some(true)).closes_to_tray());
+        assert!(!conf(true, true, Some(false)).closes_to_tray());
+        // Without a tray there is no way back to a hidden window
+        assert!(!conf(false, true, None).closes_to_tray());
+        assert!(!conf(false, false, Some(true)).closes_to_tray());
+    }
+
+    #[test]
+    fn closes_to_tray_ignores_other_platforms() {
+        let conf = AppConf {
+            enable_tray: true,
+            close_to_tray: false,
+            close_to_tray_platforms: HashMap::from([("other-os".to_string(), true)]),
+            ..AppConf::new()
+        };
+
+        assert!(!conf.closes_to_tray());
+    }
+
+    #[test]
+    fn resolve_theme_follows_the_os_for_system() {
+        assert_eq!(
+            resolve_theme("system", || dark_light::Mode::Dark),
+            Theme::Dark
+        );
+        assert_eq!(
+            resolve_theme("system", || dark_light::Mode::Light),
+            Theme::Light
+        );
+        assert_eq!(
+            resolve_theme("system", || dark_light::Mode::Default),
+            Theme::Light
+        );
+    }
+
+    #[test]
+    fn resolve_theme_ignores_the_os_otherwise() {
+        let system = || -> dark_light::Mode { unreachable!() };
+        assert_eq!(resolve_theme("dark", system), Theme::Dark);
+        assert_eq!(resolve_theme("light", system), Theme::Light);
+        assert_eq!(resolve_theme("sepia", system), Theme::Light);
     }
 }
@@ -1,15 +1,130 @@
+use std::time::Duration;
+
+/// Current `AppConf` layout, see `conf::migrate`.
+pub static CONF_VERSION: u32 = 2;
 pub static TITLEBAR_HEIGHT: f64 = 28.0;
+pub static TITLEBAR_HEIGHT_RANGE: (f64, f64) = (20.0, 80.0);
 pub static ASK_HEIGHT: f64 = 120.0;
+pub static ASK_HEIGHT_RANGE: (f64, f64) = (60.0, 400.0);
+/// Share of the window width the main view keeps next to the reference pane.
+pub static SPLIT_RATIO: f64 = 0.5;
+pub static SPLIT_RATIO_RANGE: (f64, f64) = (0.2, 0.8);
+pub static ZOOM_RANGE: (f64, f64) = (0.3, 3.0);
+pub static ZOOM_STEP: f64 = 0.1;
+pub static OPACITY_RANGE: (f64, f64) = (0.2, 1.0);
+pub static WINDOW_WIDTH: f64 = 800.0;
+pub static WINDOW_HEIGHT: f64 = 600.0;
+/// Logical sizes offered by `resize_preset` out of the box.
+pub static DEFAULT_WINDOW_PRESETS: &[(&str, f64, f64)] = &[
+    ("mobile", 390.0, 844.0),
+    ("tablet", 768.0, 1024.0),
+    ("desktop", 1280.0, 800.0),
+];
+pub static WINDOW_MIN_WIDTH: f64 = 300.0;
+pub static WINDOW_MIN_HEIGHT: f64 = 200.0;
+/// Logical pixels always left to the main view when computing the minimum height.
+pub static MAIN_VIEW_MIN_HEIGHT: f64 = 100.0;
+pub static GEOMETRY_SAVE_DELAY: u64 = 500;
+/// Milliseconds the window has to stay focused before `focus_composer_on_show`
+/// focuses the prompt, so quickly toggling it doesn't run the script each time.
+pub static FOCUS_COMPOSER_DELAY: u64 = 150;
+/// Seconds to wait for the start URL's host before showing the offline page.
+pub static OFFLINE_PROBE_TIMEOUT: u64 = 5;
+/// Seconds to wait for the main view to report the model, long enough for the
+/// switcher to render after a load.
+pub static MODEL_REPLY_TIMEOUT: u64 = 10;
+/// Milliseconds between view layout passes while the window is being resized.
+pub static RESIZE_LAYOUT_INTERVAL: u64 = 16;
+/// Main view recoveries allowed after crashes in quick succession, see `crash`.
+pub static WEBVIEW_RECOVERY_LIMIT: u32 = 3;
+/// Time the main view has to stay up before its crashes are counted afresh.
+pub static WEBVIEW_RECOVERY_WINDOW: Duration = Duration::from_secs(60);
+/// ChatGPT takes up to 10 files per message.
+pub static UPLOAD_MAX_FILES: usize = 10;
+/// Total size of the files of one drop. ChatGPT accepts larger files, but they
+/// reach the page in a single `eval` as base64 and much more stalls the webview.
+pub static UPLOAD_MAX_SIZE: u64 = 50 * 1024 * 1024;
+/// Extensions ChatGPT never accepts, filtered before reading the file.
+pub static UPLOAD_BLOCKED_EXTENSIONS: &[&str] = &[
+    "app", "bin", "dll", "dmg", "dylib", "exe", "iso", "msi", "pkg", "so",
+];
+
+/// Accelerator of every action `shortcuts` can rebind. `toggle_window` is global,
+/// `stop_generating` only handled while the main view has focus and the rest are
+/// menu accelerators.
+pub static DEFAULT_SHORTCUTS: &[(&str, &str)] = &[
+    ("toggle_window", "CmdOrCtrl+Shift+Space"),
+    ("copy_response", "CmdOrCtrl+Shift+C"),
+    ("focus_composer", "CmdOrCtrl+L"),
+    ("paste_plain", "CmdOrCtrl+Shift+V"),
+    ("stop_generating", "Escape"),
+    // ChatGPT's own sidebar shortcut, taken over so the state is remembered
+    ("toggle_sidebar", "CmdOrCtrl+Shift+S"),
+    ("toggle_mute", "CmdOrCtrl+Shift+M"),
+    ("new_window", "CmdOrCtrl+Shift+N"),
+    ("snap_left", "CmdOrCtrl+Alt+Left"),
+    ("snap_right", "CmdOrCtrl+Alt+Right"),
+    ("snap_full", "CmdOrCtrl+Alt+Up"),
+    ("snap_center", "CmdOrCtrl+Alt+C"),
+    ("zoom_in", "CmdOrCtrl+="),
+    ("zoom_out", "CmdOrCtrl+-"),
+    ("zoom_reset", "CmdOrCtrl+0"),
+];
+pub static DEFAULT_START_URL: &str = "https://chatgpt.com";
+pub static DEFAULT_PROFILE: &str = "default";
+/// Keeps the config, scripts and webview data in one folder, e.g. for a portable
+/// install. `data_dir` in the config still takes precedence for everything else.
+pub static DATA_DIR_ENV: &str = "CHATGPT_DATA_DIR";
+/// Launches the main view with `INIT_SCRIPT` alone, leaving out the scripts
+/// folder, `custom.css` and the app's own page scripts. Helps to tell whether
+/// a broken UI comes from a script.
+pub static SAFE_MODE_FLAG: &str = "--safe-mode";
+/// Verification pages ChatGPT navigates to on its own.
+pub static DEFAULT_ALLOWED_HOSTS: &[&str] = &["challenges.cloudflare.com"];
+/// Login providers whose popups are opened in the main view instead.
+pub static DEFAULT_AUTH_DOMAINS: &[&str] = &[
+    "openai.com",
+    "auth0.com",
+    "accounts.google.com",
+    "login.live.com",
+    "login.microsoftonline.com",
+    "appleid.apple.com",
+];
+
+/// Release feeds in the `tauri-plugin-updater` format, the beta builds are
+/// published under the `beta` tag.
+#[cfg(feature = "updater")]
+pub static UPDATE_ENDPOINT_STABLE: &str =
+    "https://github.com/lencx/ChatGPT/releases/latest/download/latest.json";
+#[cfg(feature = "updater")]
+pub static UPDATE_ENDPOINT_BETA: &str =
+    "https://github.com/lencx/ChatGPT/releases/download/beta/latest.json";
 
 pub static WINDOW_SETTINGS: &str = "settings";
+pub static DEFAULT_WINDOW_TITLE: &str = "ChatGPT";
+pub static DEFAULT_TITLE_TEMPLATE: &str = "{chat} — ChatGPT";
 
 pub static INIT_SCRIPT: &str = r#"
 window.addEventListener('DOMContentLoaded', function() {
+    // `main` in the core window, `core-2-main` etc. in the ones from `new_window`
+    const view = window.__TAURI__.webview.getCurrentWebview().label;
+    const titlebar = view.replace(/main$/, 'titlebar');
+
     function handleUrlChange() {
         const url = window.location.href;
         if (url !== 'about:blank') {
             console.log('URL changed:', url);
-            window.__TAURI__.webviewWindow.WebviewWindow.getByLabel('titlebar').emit('navigation:change', { url });
+            window.__TAURI__.event.emitTo(titlebar, 'navigation:change', { url, view });
+        }
+    }
+
+    let lastTitle;
+    function handleTitleChange() {
+        const url = window.location.href;
+        const title = document.title;
+        if (url !== 'about:blank' && url + title !== lastTitle) {
+            lastTitle = url + title;
+            window.__TAURI__.event.emitTo(titlebar, 'title:change', { url, title });
         }
     }
 
@@ -40,6 +155,99 @@ window.addEventListener('DOMContentLoaded', function() {
         handleUrlChange();
     };
 
+    // ChatGPT names the conversation some time after the URL changes
+    new MutationObserver(handleTitleChange).observe(document.head, { childList: true, subtree: true, characterData: true });
+
     handleUrlChange();
+    handleTitleChange();
 });
 "#;
+
+pub static SPELLCHECK_SCRIPT: &str = r#"
+window.addEventListener('DOMContentLoaded', function() {
+    function enableSpellcheck() {
+        document.querySelectorAll('textarea, [contenteditable="true"]').forEach(function(el) {
+            if (el.getAttribute('spellcheck') !== 'true') {
+                el.setAttribute('spellcheck', 'true');
+            }
+        });
+    }
+
+    // The composer is re-rendered on navigation, so keep re-applying it
+    new MutationObserver(enableSpellcheck).observe(document.body, { childList: true, subtree: true });
+    enableSpellcheck();
+});
+"#;
+
+/// Finishes animations at once rather than dropping them, so elements still end
+/// up in their final state.
+pub static REDUCE_MOTION_CSS: &str = r#"
+*, *::before, *::after {
+    animation-duration: 0.01ms !important;
+    animation-delay: 0s !important;
+    animation-iteration-count: 1 !important;
+    transition: none !important;
+    scroll-behavior: auto !important;
+}
+"#;
+
+/// Reports input to `idle_activity` at most every 30 seconds.
+pub static IDLE_ACTIVITY_SCRIPT: &str = r#"
+(function() {
+    let last = 0;
+    function report() {
+        const now = Date.now();
+        if (now - last < 30000) return;
+        last = now;
+        window.__TAURI__.core.invoke('idle_activity');
+    }
+
+    ['keydown', 'pointerdown', 'wheel'].forEach(function(type) {
+        window.addEventListener(type, report, { capture: true, passive: true });
+    });
+})();
+"#;
+
+/// Reports to `response_complete` once the stop button goes away, i.e. a
+/// response finished streaming or was stopped.
+pub static RESPONSE_COMPLETE_SCRIPT: &str = r#"
+(function() {
+    let generating = false;
+    function check() {
+        const ask = window.ChatAsk;
+        if (!ask) return;
+        const now = ask.queryAll(ask.SELECTORS.stopButton).length > 0;
+        if (generating && !now) {
+            const preview = ask.lastResponse().slice(0, 200);
+            window.__TAURI__.core.invoke('response_complete', { preview: preview || null });
+        }
+        generating = now;
+    }
+
+    window.addEventListener('DOMContentLoaded', function() {
+        new MutationObserver(check).observe(document.body, { childList: true, subtree: true });
+    });
+})();
+"#;
+
+pub static COPY_LAST_RESPONSE_SCRIPT: &str = r#"
+(function() {
+    const text = window.ChatAsk?.lastResponse();
+    window.__TAURI__.core.invoke('copy_response_text', { text: text || null });
+})();
+"#;
+
+pub static FOCUS_COMPOSER_SCRIPT: &str = "window.ChatAsk?.focus();";
+
+pub static STOP_GENERATING_SCRIPT: &str = "window.ChatAsk?.stop();";
+
+pub static TOGGLE_SIDEBAR_SCRIPT: &str = r#"
+(function() {
+    const open = window.ChatAsk?.toggleSidebar();
+    if (typeof open === 'boolean') {
+        window.__TAURI__.core.invoke('sidebar_toggled', { collapsed: !open });
+    }
+})();
+"#;
+
+pub static COLLAPSE_SIDEBAR_SCRIPT: &str = "window.ChatAsk?.collapseSidebar();";
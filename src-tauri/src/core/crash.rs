@@ -0,0 +1,81 @@
+use log::error;
+use std::{sync::Mutex, time::Instant};
+use tauri::{AppHandle, Emitter, Webview};
+
+use crate::core::{
+    cmd,
+    conf::AppConf,
+    constant::{WEBVIEW_RECOVERY_LIMIT, WEBVIEW_RECOVERY_WINDOW},
+    navigation, platform,
+    setup::lock,
+};
+
+/// Recoveries so far and when the last one happened. The count starts over
+/// once the main view has stayed up for `WEBVIEW_RECOVERY_WINDOW`.
+static RECOVERIES: Mutex<Option<(u32, Instant)>> = Mutex::new(None);
+
+/// Watches the main view for its content process going away, which otherwise
+/// leaves a blank window behind.
+pub fn watch(app: &AppHandle, webview: &Webview) {
+    let handle = app.clone();
+    let result = platform::on_webview_crash(webview, move |reason| handle_crash(&handle, reason));
+    if let Err(e) = result {
+        error!("[view:crash] Failed to watch the main view: {}", e);
+    }
+}
+
+/// Emits `webview-crashed` and, with `auto_recover_webview`, recreates the main
+/// view at its last URL unless it keeps crashing.
+fn handle_crash(app: &AppHandle, reason: String) {
+    error!(
+        "[view:crash] The main view's content process is gone: {}",
+        reason
+    );
+
+    let auto_recover = AppConf::load(app)
+        .map(|conf| conf.auto_recover_webview)
+        .unwrap_or(true);
+    let recovering = auto_recover && take_recovery();
+    let payload = serde_json::json!({ "reason": reason, "recovering": recovering });
+    let message = if recovering {
+        "The page crashed, reopening it"
+    } else {
+        "The page crashed, reload to try again"
+    };
+    let result = app
+        .emit("webview-crashed", payload)
+        .and_then(|_| app.emit("toast", serde_json::json!({ "message": message })));
+    if let Err(e) = result {
+        error!("[view:crash] Failed to report the crash: {}", e);
+    }
+    if !recovering {
+        return;
+    }
+
+    let url = navigation::current(app);
+    let app = app.clone();
+    // Native crash callbacks run on the main thread, which creating a webview waits on
+    tauri::async_runtime::spawn(async move {
+        let result = AppConf::load(&app)
+            .map_err(|e| e.to_string())
+            .and_then(|conf| {
+                let url = url.unwrap_or_else(|| conf.start_url());
+                cmd::recreate_main_view(&app, &conf, url)
+            });
+        if let Err(e) = result {
+            error!("[view:crash] Failed to recreate the main view: {}", e);
+        }
+    });
+}
+
+/// Counts a recovery, returning false once `WEBVIEW_RECOVERY_LIMIT` is used up.
+fn take_recovery() -> bool {
+    let mut recoveries = lock(&RECOVERIES);
+    let now = Instant::now();
+    let count = match *recoveries {
+        Some((count, last)) if now.duration_since(last) < WEBVIEW_RECOVERY_WINDOW => count + 1,
+        _ => 1,
+    };
+    *recoveries = Some((count, now));
+    count <= WEBVIEW_RECOVERY_LIMIT
+}
@@ -0,0 +1,329 @@
+use log::{error, info, warn};
+use std::{
+    collections::{HashMap, VecDeque},
+    fs,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+use tauri::{command, webview::DownloadEvent, AppHandle, Emitter, Manager, State, Url};
+use tauri_plugin_shell::ShellExt;
+
+use crate::core::{
+    conf::{AppConf, DownloadAction},
+    platform,
+    setup::lock,
+};
+
+/// The most recent successful download, for `reveal_download`.
+#[derive(Default)]
+pub struct LastDownload(Mutex<Option<PathBuf>>);
+
+/// Files downloaded in this session, oldest first. `max_downloads` only ever
+/// deletes these, never files the user put in the folder or from earlier runs.
+#[derive(Default)]
+pub struct DownloadedFiles(Mutex<VecDeque<PathBuf>>);
+
+/// Records `path` and deletes the oldest recorded files beyond `max`, 0 keeps all.
+fn prune_downloads(app: &AppHandle, path: PathBuf, max: usize) {
+    let files = app.state::<DownloadedFiles>();
+    let mut files = lock(&files.0);
+    files.retain(|file| file.exists() && *file != path);
+    files.push_back(path);
+    if max == 0 {
+        return;
+    }
+    while files.len() > max {
+        let Some(oldest) = files.pop_front() else {
+            break;
+        };
+        match fs::remove_file(&oldest) {
+            Ok(()) => info!("[view:download] Pruned {}", oldest.display()),
+            Err(e) => error!(
+                "[view:download] Failed to prune {}: {}",
+                oldest.display(),
+                e
+            ),
+        }
+    }
+}
+
+/// Destinations of the downloads still in flight. The webview gives downloads no
+/// id, so they are keyed by URL, with the same URL fetched twice finishing in the
+/// order it was requested.
+#[derive(Default)]
+pub struct PendingDownloads(Mutex<HashMap<Url, VecDeque<PathBuf>>>);
+
+impl PendingDownloads {
+    fn contains(&self, path: &Path) -> bool {
+        lock(&self.0)
+            .values()
+            .flatten()
+            .any(|pending| pending == path)
+    }
+
+    fn push(&self, url: Url, path: PathBuf) {
+        lock(&self.0).entry(url).or_default().push_back(path);
+    }
+
+    fn pop(&self, url: &Url) -> Option<PathBuf> {
+        let mut pending = lock(&self.0);
+        let paths = pending.get_mut(url)?;
+        let path = paths.pop_front();
+        if paths.is_empty() {
+            pending.remove(url);
+        }
+        path
+    }
+}
+
+/// Handles download events of a main view, `pending` keeps the resolved
+/// destination of each download between its `Requested` and `Finished` events.
+/// Progress goes to the view's `titlebar`.
+///
+/// Finished downloads are only opened when `open_downloads_on_finish` is set,
+/// a `download-finished` event carrying the path is emitted either way.
+pub fn handle_download_event(
+    app: &AppHandle,
+    titlebar: &str,
+    pending: &PendingDownloads,
+    event: DownloadEvent,
+) -> bool {
+    match event {
+        DownloadEvent::Requested { url, destination } => {
+            let Some(download_dir) = download_dir(app) else {
+                error!("[view:download] No download directory, cancelling {}", url);
+                return false;
+            };
+            // Files of downloads in flight may not exist yet, they are taken all the same
+            let path = free_path(download_dir.join(&destination), |path| {
+                pending.contains(path)
+            });
+            pending.push(url, path.clone());
+            *destination = path;
+
+            // The webview only reports start and finish, so the total is unknown and
+            // the frontend renders an indeterminate state until `download-finished`
+            emit(
+                app,
+                titlebar,
+                "download-progress",
+                serde_json::json!({ "received": 0, "total": null }),
+            );
+        }
+        DownloadEvent::Finished { url, path, success } => {
+            let requested = pending.pop(&url);
+            // macOS never reports the path
+            let Some(final_path) = path.or(requested) else {
+                error!("[view:download] No destination for the download of {}", url);
+                return true;
+            };
+
+            complete(app, titlebar, final_path, success);
+        }
+        _ => (),
+    }
+    true
+}
+
+/// Finishes a download or export written to `path`. A successful one becomes the
+/// last download, counts towards `max_downloads` and gets its `download_action`,
+/// then `titlebar` is sent `download-finished` either way.
+pub fn complete(app: &AppHandle, titlebar: &str, path: PathBuf, success: bool) {
+    if success {
+        *lock(&app.state::<LastDownload>().0) = Some(path.clone());
+        let conf = AppConf::load(app).ok();
+        let max_downloads = conf.as_ref().map_or(0, |conf| conf.max_downloads);
+        prune_downloads(app, path.clone(), max_downloads);
+        let action = conf
+            .map(|conf| conf.download_action(&path))
+            .unwrap_or(DownloadAction::None);
+        let result = match action {
+            DownloadAction::Open => app
+                .shell()
+                .open(path.to_string_lossy(), None)
+                .map_err(|e| e.to_string()),
+            DownloadAction::Reveal => reveal(app, &path),
+            DownloadAction::None => Ok(()),
+        };
+        if let Err(e) = result {
+            error!(
+                "[view:download] Failed to {:?} {}: {}",
+                action,
+                path.display(),
+                e
+            );
+        }
+    }
+
+    emit(
+        app,
+        titlebar,
+        "download-finished",
+        serde_json::json!({ "path": path, "success": success }),
+    );
+}
+
+/// Shows the last download selected in the file manager, or opens its folder
+/// where selecting isn't possible.
+#[command]
+pub fn reveal_download(app: AppHandle, last: State<LastDownload>) -> Result<(), String> {
+    let path = lock(&last.0)
+        .clone()
+        .ok_or("[download:reveal] No download has finished yet")?;
+    reveal(&app, &path)
+}
+
+fn reveal(app: &AppHandle, path: &Path) -> Result<(), String> {
+    if let Err(e) = platform::reveal_in_file_manager(path) {
+        warn!(
+            "[download:reveal] Failed to select {}, opening its folder: {}",
+            path.display(),
+            e
+        );
+        let dir = path
+            .parent()
+            .ok_or("[download:reveal] The download has no parent folder")?;
+        app.shell()
+            .open(dir.to_string_lossy(), None)
+            .map_err(|e| format!("[download:reveal] {}", e))?;
+    }
+    Ok(())
+}
+
+/// The configured download folder, the OS one when the config can't be read.
+/// `None` when neither is available.
+pub fn download_dir(app: &AppHandle) -> Option<PathBuf> {
+    let e = match AppConf::get_download_dir(app) {
+        Ok(dir) => return Some(dir),
+        Err(e) => e,
+    };
+    warn!(
+        "[view:download] Failed to get the download directory, using the OS default: {}",
+        e
+    );
+    app.path()
+        .download_dir()
+        .map_err(|e| error!("[view:download] No OS download directory: {}", e))
+        .ok()
+}
+
+fn emit(app: &AppHandle, titlebar: &str, event: &str, payload: serde_json::Value) {
+    if let Err(e) = app.emit_to(titlebar, event, payload) {
+        error!("[view:download] Failed to emit {}: {}", event, e);
+    }
+}
+
+/// Appends ` (1)`, ` (2)`, ... before the extension until the path is free.
+pub fn unique_path(path: PathBuf) -> PathBuf {
+    free_path(path, |_| false)
+}
+
+/// `unique_path`, also skipping the paths `taken` claims.
+fn free_path(path: PathBuf, taken: impl Fn(&Path) -> bool) -> PathBuf {
+    let is_free = |candidate: &Path| !candidate.exists() && !taken(candidate);
+    if is_free(&path) {
+        return path;
+    }
+
+    let stem = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let extension = path
+        .extension()
+        .map(|ext| ext.to_string_lossy().into_owned());
+
+    (1..)
+        .map(|i| {
+            let filename = match &extension {
+                Some(ext) => format!("{} ({}).{}", stem, i, ext),
+                None => format!("{} ({})", stem, i),
+            };
+            path.with_file_name(filename)
+        })
+        .find(|candidate| is_free(candidate))
+        .unwrap_or(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A fresh directory under the system temp dir, removed on drop.
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new(name: &str) -> Self {
+            let dir = std::env::temp_dir().join(format!(
+                "chatgpt-download-{}-{}",
+                name,
+                std::process::id()
+            ));
+            let _ = fs::remove_dir_all(&dir);
+            fs::create_dir_all(&dir).unwrap();
+            Self(dir)
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    #[test]
+    fn unique_path_suffixes_before_the_extension() {
+        let dir = TempDir::new("extension");
+        let path = dir.0.join("report.pdf");
+        assert_eq!(unique_path(path.clone()), path);
+
+        fs::write(&path, "").unwrap();
+        assert_eq!(unique_path(path.clone()), dir.0.join("report (1).pdf"));
+
+        fs::write(dir.0.join("report (1).pdf"), "").unwrap();
+        assert_eq!(unique_path(path), dir.0.join("report (2).pdf"));
+    }
+
+    #[test]
+    fn unique_path_suffixes_a_name_without_extension() {
+        let dir = TempDir::new("no-extension");
+        let path = dir.0.join("README");
+        fs::write(&path, "").unwrap();
+
+        assert_eq!(unique_path(path), dir.0.join("README (1)"));
+    }
+
+    #[test]
+    fn free_path_skips_taken_paths() {
+        let dir = TempDir::new("taken");
+        let path = dir.0.join("data.csv");
+        let taken = [path.clone(), dir.0.join("data (1).csv")];
+
+        assert_eq!(
+            free_path(path, |candidate| taken.iter().any(|t| t == candidate)),
+            dir.0.join("data (2).csv")
+        );
+    }
+
+    #[test]
+    fn pending_downloads_keep_overlapping_downloads_of_one_url_apart() {
+        let dir = TempDir::new("pending");
+        let pending = PendingDownloads::default();
+        let url = Url::parse("https://example.com/file.zip").unwrap();
+        let other = Url::parse("https://example.com/other.zip").unwrap();
+
+        // Neither file exists yet, the first download must still claim its path
+        for _ in 0..2 {
+            let path = free_path(dir.0.join("file.zip"), |path| pending.contains(path));
+            pending.push(url.clone(), path);
+        }
+
+        assert!(pending.contains(&dir.0.join("file.zip")));
+        assert!(pending.contains(&dir.0.join("file (1).zip")));
+        assert_eq!(pending.pop(&other), None);
+        assert_eq!(pending.pop(&url), Some(dir.0.join("file.zip")));
+        assert_eq!(pending.pop(&url), Some(dir.0.join("file (1).zip")));
+        assert_eq!(pending.pop(&url), None);
+        assert!(!pending.contains(&dir.0.join("file.zip")));
+    }
+}
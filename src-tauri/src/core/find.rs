@@ -0,0 +1,86 @@
+use log::error;
+use std::sync::Mutex;
+use tauri::{command, AppHandle, Emitter, Manager, State};
+
+use crate::core::setup::lock;
+
+/// The active search query, so `find_next`/`find_prev` can repeat it.
+#[derive(Default)]
+pub struct FindState(Mutex<String>);
+
+/// Shows the find overlay in the titlebar view, bound to CmdOrCtrl+F.
+pub fn open(app: &AppHandle) -> Result<(), String> {
+    let titlebar = app
+        .get_webview("titlebar")
+        .ok_or("[find:open] The titlebar webview does not exist")?;
+    titlebar
+        .set_focus()
+        .and_then(|_| app.emit_to("titlebar", "find-open", ()))
+        .map_err(|e| format!("[find:open] {}", e))
+}
+
+/// Searches the main view for `query` from the top, an empty query clears the
+/// current highlight instead.
+#[command]
+pub fn find_start(app: AppHandle, state: State<FindState>, query: String) -> Result<(), String> {
+    *lock(&state.0) = query.clone();
+    if query.is_empty() {
+        return eval(&app, "window.getSelection()?.removeAllRanges();");
+    }
+    eval(
+        &app,
+        &format!(
+            "window.getSelection()?.removeAllRanges(); window.find({}, false, false, true);",
+            js_string(&query)?
+        ),
+    )
+}
+
+#[command]
+pub fn find_next(app: AppHandle, state: State<FindState>) -> Result<(), String> {
+    find_again(&app, &state, false)
+}
+
+#[command]
+pub fn find_prev(app: AppHandle, state: State<FindState>) -> Result<(), String> {
+    find_again(&app, &state, true)
+}
+
+/// Clears the query and highlight and hands focus back to the main view.
+#[command]
+pub fn find_close(app: AppHandle, state: State<FindState>) -> Result<(), String> {
+    lock(&state.0).clear();
+    eval(&app, "window.getSelection()?.removeAllRanges();")?;
+    if let Some(main) = app.get_webview("main") {
+        if let Err(e) = main.set_focus() {
+            error!("[find:close] Failed to focus main view: {}", e);
+        }
+    }
+    Ok(())
+}
+
+fn find_again(app: &AppHandle, state: &FindState, backwards: bool) -> Result<(), String> {
+    let query = lock(&state.0).clone();
+    if query.is_empty() {
+        return Ok(());
+    }
+    eval(
+        app,
+        &format!(
+            "window.find({}, false, {}, true);",
+            js_string(&query)?,
+            backwards
+        ),
+    )
+}
+
+fn eval(app: &AppHandle, script: &str) -> Result<(), String> {
+    app.get_webview("main")
+        .ok_or("[find] The main webview does not exist")?
+        .eval(script)
+        .map_err(|e| format!("[find] {}", e))
+}
+
+fn js_string(value: &str) -> Result<String, String> {
+    serde_json::to_string(value).map_err(|e| format!("[find] {}", e))
+}
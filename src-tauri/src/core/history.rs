@@ -0,0 +1,103 @@
+use log::error;
+use std::{collections::VecDeque, sync::Mutex};
+use tauri::{command, AppHandle, Listener, Manager, Url};
+
+use crate::core::{conf::AppConf, setup::lock};
+
+/// Recently visited conversations, oldest first, walked by `next_conversation`
+/// and `prev_conversation`.
+#[derive(Default)]
+pub struct ConversationHistory(Mutex<History>);
+
+#[derive(Default)]
+struct History {
+    urls: VecDeque<String>,
+    /// Entry opened by the last cycle, `None` once a conversation is opened directly.
+    cursor: Option<usize>,
+}
+
+impl History {
+    /// Moves `url` to the newest slot, unless it is the entry being cycled to.
+    fn visit(&mut self, url: String, limit: usize) {
+        if self.cursor.is_some_and(|cursor| self.urls[cursor] == url) {
+            return;
+        }
+        self.cursor = None;
+        self.urls.retain(|visited| *visited != url);
+        self.urls.push_back(url);
+        while self.urls.len() > limit {
+            self.urls.pop_front();
+        }
+    }
+
+    fn step(&mut self, backwards: bool) -> Option<String> {
+        let newest = self.urls.len().checked_sub(1)?;
+        let current = self.cursor.unwrap_or(newest);
+        let next = if backwards {
+            current.checked_sub(1)?
+        } else {
+            Some(current + 1).filter(|next| *next <= newest)?
+        };
+        self.cursor = Some(next);
+        Some(self.urls[next].clone())
+    }
+}
+
+/// Records conversations from the URL changes the main view reports, including
+/// in-page route changes that never reach `on_navigation`.
+pub fn init(app: &AppHandle) {
+    let handle = app.clone();
+    app.listen_any("navigation:change", move |event| {
+        let url = serde_json::from_str::<serde_json::Value>(event.payload())
+            .ok()
+            .and_then(|payload| payload["url"].as_str().and_then(|url| Url::parse(url).ok()));
+        let Some(url) = url.filter(is_conversation) else {
+            return;
+        };
+        let limit = match AppConf::load(&handle) {
+            Ok(conf) => conf.conversation_history_size,
+            Err(e) => {
+                error!("[history:visit] {}", e);
+                return;
+            }
+        };
+        let history = handle.state::<ConversationHistory>();
+        lock(&history.0).visit(url.into(), limit);
+    });
+}
+
+/// Opens the next more recent conversation, returning its URL.
+#[command]
+pub fn next_conversation(app: AppHandle) -> Result<Option<String>, String> {
+    cycle(&app, false)
+}
+
+/// Opens the next older conversation, returning its URL.
+#[command]
+pub fn prev_conversation(app: AppHandle) -> Result<Option<String>, String> {
+    cycle(&app, true)
+}
+
+pub fn cycle(app: &AppHandle, backwards: bool) -> Result<Option<String>, String> {
+    let history = app.state::<ConversationHistory>();
+    let Some(url) = lock(&history.0).step(backwards) else {
+        return Ok(None);
+    };
+    let target = Url::parse(&url).map_err(|e| format!("[history:cycle] {}", e))?;
+    app.get_webview("main")
+        .ok_or("[history:cycle] The main webview does not exist")?
+        .navigate(target)
+        .map_err(|e| format!("[history:cycle] {}", e))?;
+    Ok(Some(url))
+}
+
+/// ChatGPT conversation routes, `/c/<id>` optionally nested under a GPT's `/g/<id>`.
+fn is_conversation(url: &Url) -> bool {
+    url.path_segments().is_some_and(|segments| {
+        let segments: Vec<_> = segments.filter(|segment| !segment.is_empty()).collect();
+        segments
+            .windows(2)
+            .last()
+            .is_some_and(|tail| tail[0] == "c")
+    })
+}
@@ -0,0 +1,53 @@
+use log::{error, info};
+use std::{
+    sync::atomic::{AtomicU64, Ordering},
+    time::Duration,
+};
+use tauri::{command, AppHandle, Manager};
+
+use crate::core::conf::AppConf;
+
+/// Bumped on every focus or input, a countdown only reloads if it's still current.
+static GENERATION: AtomicU64 = AtomicU64::new(0);
+
+/// Restarts the countdown after which the main view goes back to the start URL,
+/// since ChatGPT's session goes stale when left open for hours. Does nothing
+/// with `idle_reload_minutes` at 0.
+pub fn reset(app: &AppHandle) {
+    let current = GENERATION.fetch_add(1, Ordering::SeqCst) + 1;
+    let minutes = AppConf::load(app)
+        .map(|conf| conf.idle_reload_minutes)
+        .unwrap_or(0);
+    if minutes == 0 {
+        return;
+    }
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(Duration::from_secs(minutes * 60)).await;
+        if GENERATION.load(Ordering::SeqCst) != current {
+            return;
+        }
+        let Some(mut main) = app.get_webview("main") else {
+            return;
+        };
+        let Ok(conf) = AppConf::load(&app) else {
+            return;
+        };
+        info!("[view:idle] Idle for {} minutes, reloading", minutes);
+        if let Err(e) = main.navigate(conf.start_url()) {
+            error!("[view:idle] Failed to reload the main view: {}", e);
+        }
+    });
+}
+
+/// Leaves any running countdown without effect, used on exit.
+pub fn cancel() {
+    GENERATION.fetch_add(1, Ordering::SeqCst);
+}
+
+/// Reported by the main view on input, throttled there, see `IDLE_ACTIVITY_SCRIPT`.
+#[command]
+pub fn idle_activity(app: AppHandle) {
+    reset(&app);
+}
@@ -0,0 +1,64 @@
+use std::{
+    fs::{self, File},
+    sync::Mutex,
+};
+use tauri::{AppHandle, Manager};
+use tracing_subscriber::{filter::LevelFilter, fmt, layer::SubscriberExt, util::SubscriberInitExt};
+
+use crate::core::conf::LogLevel;
+
+/// Sends the `log` macros used throughout the app to stderr and to
+/// `chatgpt.log` in the app log dir, which is truncated on every start so a
+/// report only carries the current session. Panics are logged too, since the
+/// `.expect`s in setup would otherwise only reach a console that release
+/// builds on Windows don't have.
+pub fn init(app: &AppHandle, level: LogLevel) {
+    let file = app
+        .path()
+        .app_log_dir()
+        .map_err(|e| e.to_string())
+        .and_then(|dir| {
+            fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+            File::create(dir.join("chatgpt.log")).map_err(|e| e.to_string())
+        });
+
+    let (file_layer, file_error) = match file {
+        Ok(file) => (
+            Some(fmt::layer().with_ansi(false).with_writer(Mutex::new(file))),
+            None,
+        ),
+        Err(e) => (None, Some(e)),
+    };
+
+    let result = tracing_subscriber::registry()
+        .with(LevelFilter::from(level))
+        .with(fmt::layer().with_writer(std::io::stderr))
+        .with(file_layer)
+        .try_init();
+
+    if let Err(e) = result {
+        eprintln!("[core:log] Failed to install logger: {}", e);
+        return;
+    }
+    if let Some(e) = file_error {
+        log::error!("[core:log] Logging to stderr only: {}", e);
+    }
+
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        log::error!("[core:panic] {}", info);
+        default_hook(info);
+    }));
+}
+
+impl From<LogLevel> for LevelFilter {
+    fn from(level: LogLevel) -> Self {
+        match level {
+            LogLevel::Error => LevelFilter::ERROR,
+            LogLevel::Warn => LevelFilter::WARN,
+            LogLevel::Info => LevelFilter::INFO,
+            LogLevel::Debug => LevelFilter::DEBUG,
+            LogLevel::Trace => LevelFilter::TRACE,
+        }
+    }
+}
@@ -0,0 +1,366 @@
+use log::error;
+use tauri::{
+    menu::{CheckMenuItem, Menu, MenuEvent, MenuItem, PredefinedMenuItem, Submenu},
+    AppHandle, Manager, Window, Wry,
+};
+
+use crate::core::{cmd, conf::AppConf, find, history, navigation, setup, shortcuts};
+
+/// Accelerators of the menu items that can't be rebound, by item id. Actions
+/// can't be bound onto them, see `shortcuts`.
+pub static FIXED_ACCELERATORS: &[(&str, &str)] = &[
+    #[cfg(target_os = "macos")]
+    ("quit", "CmdOrCtrl+Q"),
+    ("edit_find", "CmdOrCtrl+F"),
+    ("view_new_chat", "CmdOrCtrl+N"),
+    ("view_reload", "CmdOrCtrl+R"),
+    #[cfg(target_os = "macos")]
+    ("view_go_back", "Cmd+["),
+    #[cfg(target_os = "macos")]
+    ("view_go_forward", "Cmd+]"),
+    #[cfg(not(target_os = "macos"))]
+    ("view_go_back", "Alt+Left"),
+    #[cfg(not(target_os = "macos"))]
+    ("view_go_forward", "Alt+Right"),
+    ("view_prev_conversation", "Ctrl+Shift+Tab"),
+    ("view_next_conversation", "Ctrl+Tab"),
+    ("view_export_pdf", "CmdOrCtrl+P"),
+    ("view_toggle_devtools", "CmdOrCtrl+Shift+I"),
+];
+
+fn fixed(id: &str) -> Option<&'static str> {
+    FIXED_ACCELERATORS
+        .iter()
+        .find(|(item, _)| *item == id)
+        .map(|(_, accelerator)| *accelerator)
+}
+
+/// Builds the application menu. On Linux and Windows it is attached to each window,
+/// so the edit shortcuts are routed into the focused webview there as well.
+pub fn init(app: &AppHandle) -> tauri::Result<Menu<Wry>> {
+    let conf = AppConf::load(app).ok();
+    let stay_on_top = conf.as_ref().is_some_and(|conf| conf.stay_on_top);
+    let safe_mode = setup::is_safe_mode();
+    #[cfg(not(windows))]
+    let visible_on_all_workspaces = conf
+        .as_ref()
+        .is_some_and(|conf| conf.visible_on_all_workspaces);
+    // Configurable accelerators, see `shortcuts`
+    let defaults = AppConf::new();
+    let accelerator =
+        |action: &str| shortcuts::accelerator(conf.as_ref().unwrap_or(&defaults), action);
+
+    #[cfg(target_os = "macos")]
+    let app_menu = Submenu::with_items(
+        app,
+        "ChatGPT",
+        true,
+        &[
+            &PredefinedMenuItem::about(app, None, None)?,
+            &PredefinedMenuItem::separator(app)?,
+            &PredefinedMenuItem::services(app, None)?,
+            &PredefinedMenuItem::separator(app)?,
+            &PredefinedMenuItem::hide(app, None)?,
+            &PredefinedMenuItem::hide_others(app, None)?,
+            &PredefinedMenuItem::show_all(app, None)?,
+            &PredefinedMenuItem::separator(app)?,
+            // Not the predefined item, which exits without `confirm_quit`
+            &MenuItem::with_id(app, "quit", "Quit ChatGPT", true, fixed("quit"))?,
+        ],
+    )?;
+
+    let edit_menu = Submenu::with_items(
+        app,
+        "Edit",
+        true,
+        &[
+            &PredefinedMenuItem::undo(app, None)?,
+            &PredefinedMenuItem::redo(app, None)?,
+            &PredefinedMenuItem::separator(app)?,
+            &PredefinedMenuItem::cut(app, None)?,
+            &PredefinedMenuItem::copy(app, None)?,
+            &PredefinedMenuItem::paste(app, None)?,
+            &PredefinedMenuItem::select_all(app, None)?,
+            &PredefinedMenuItem::separator(app)?,
+            &MenuItem::with_id(app, "edit_find", "Find…", true, fixed("edit_find"))?,
+            &MenuItem::with_id(
+                app,
+                "edit_copy_last_response",
+                "Copy Last Response",
+                true,
+                accelerator("copy_response"),
+            )?,
+            &MenuItem::with_id(
+                app,
+                "edit_paste_plain",
+                "Paste as Plain Text",
+                true,
+                accelerator("paste_plain"),
+            )?,
+            &MenuItem::with_id(
+                app,
+                "edit_focus_composer",
+                "Focus Composer",
+                true,
+                accelerator("focus_composer"),
+            )?,
+        ],
+    )?;
+
+    let view_menu = Submenu::with_items(
+        app,
+        "View",
+        true,
+        &[
+            &MenuItem::with_id(
+                app,
+                "view_new_chat",
+                "New Chat",
+                true,
+                fixed("view_new_chat"),
+            )?,
+            &MenuItem::with_id(app, "view_reload", "Reload", true, fixed("view_reload"))?,
+            &MenuItem::with_id(
+                app,
+                "view_toggle_sidebar",
+                "Toggle Sidebar",
+                true,
+                accelerator("toggle_sidebar"),
+            )?,
+            &MenuItem::with_id(
+                app,
+                "view_toggle_mute",
+                "Toggle Mute",
+                true,
+                accelerator("toggle_mute"),
+            )?,
+            &MenuItem::with_id(app, "view_go_back", "Back", true, fixed("view_go_back"))?,
+            &MenuItem::with_id(
+                app,
+                "view_go_forward",
+                "Forward",
+                true,
+                fixed("view_go_forward"),
+            )?,
+            &MenuItem::with_id(
+                app,
+                "view_prev_conversation",
+                "Previous Conversation",
+                true,
+                fixed("view_prev_conversation"),
+            )?,
+            &MenuItem::with_id(
+                app,
+                "view_next_conversation",
+                "Next Conversation",
+                true,
+                fixed("view_next_conversation"),
+            )?,
+            &MenuItem::with_id(
+                app,
+                "view_export_pdf",
+                "Export as PDF…",
+                true,
+                fixed("view_export_pdf"),
+            )?,
+            &PredefinedMenuItem::separator(app)?,
+            &MenuItem::with_id(
+                app,
+                "view_zoom_reset",
+                "Actual Size",
+                true,
+                accelerator("zoom_reset"),
+            )?,
+            &MenuItem::with_id(app, "view_zoom_in", "Zoom In", true, accelerator("zoom_in"))?,
+            &MenuItem::with_id(
+                app,
+                "view_zoom_out",
+                "Zoom Out",
+                true,
+                accelerator("zoom_out"),
+            )?,
+            &PredefinedMenuItem::separator(app)?,
+            &MenuItem::with_id(
+                app,
+                "view_clear_site_data",
+                "Clear Site Data…",
+                true,
+                None::<&str>,
+            )?,
+            &MenuItem::with_id(
+                app,
+                "view_toggle_devtools",
+                "Toggle Developer Tools",
+                true,
+                fixed("view_toggle_devtools"),
+            )?,
+            &MenuItem::with_id(
+                app,
+                "view_safe_mode",
+                if safe_mode {
+                    "Relaunch Normally"
+                } else {
+                    "Relaunch in Safe Mode"
+                },
+                true,
+                None::<&str>,
+            )?,
+            &CheckMenuItem::with_id(
+                app,
+                "view_always_on_top",
+                "Always on Top",
+                true,
+                stay_on_top,
+                None::<&str>,
+            )?,
+            #[cfg(not(windows))]
+            &CheckMenuItem::with_id(
+                app,
+                "view_all_workspaces",
+                "Show on All Workspaces",
+                true,
+                visible_on_all_workspaces,
+                None::<&str>,
+            )?,
+            &PredefinedMenuItem::maximize(app, Some("Zoom"))?,
+            &PredefinedMenuItem::fullscreen(app, None)?,
+        ],
+    )?;
+
+    let window_menu = Submenu::with_items(
+        app,
+        "Window",
+        true,
+        &[
+            &MenuItem::with_id(
+                app,
+                "window_new",
+                "New Window",
+                true,
+                accelerator("new_window"),
+            )?,
+            &PredefinedMenuItem::separator(app)?,
+            &MenuItem::with_id(
+                app,
+                "window_snap_left",
+                "Snap Left",
+                true,
+                accelerator("snap_left"),
+            )?,
+            &MenuItem::with_id(
+                app,
+                "window_snap_right",
+                "Snap Right",
+                true,
+                accelerator("snap_right"),
+            )?,
+            &MenuItem::with_id(
+                app,
+                "window_snap_full",
+                "Fill Screen",
+                true,
+                accelerator("snap_full"),
+            )?,
+            &MenuItem::with_id(
+                app,
+                "window_snap_center",
+                "Center",
+                true,
+                accelerator("snap_center"),
+            )?,
+        ],
+    )?;
+
+    Menu::with_items(
+        app,
+        &[
+            #[cfg(target_os = "macos")]
+            &app_menu,
+            &edit_menu,
+            &view_menu,
+            &window_menu,
+        ],
+    )
+}
+
+pub fn handle_menu_event(app: &AppHandle, event: MenuEvent) {
+    let app = app.clone();
+    let result = match event.id().as_ref() {
+        "edit_find" => find::open(&app),
+        "edit_focus_composer" => cmd::focus_composer(app),
+        "edit_copy_last_response" => cmd::copy_last_response(app),
+        "edit_paste_plain" => cmd::paste_plain(app),
+        "view_new_chat" => cmd::new_chat(app),
+        "view_reload" => cmd::reload_main(app),
+        "view_toggle_sidebar" => cmd::toggle_sidebar(app),
+        "view_toggle_mute" => cmd::toggle_mute(app).map(|_| ()),
+        "view_go_back" => chat_window(&app).and_then(navigation::go_back),
+        "view_go_forward" => chat_window(&app).and_then(navigation::go_forward),
+        "view_prev_conversation" => history::cycle(&app, true).map(|_| ()),
+        "view_next_conversation" => history::cycle(&app, false).map(|_| ()),
+        "view_export_pdf" => {
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = cmd::export_conversation_pdf(app, None).await {
+                    error!("[menu:view_export_pdf] {}", e);
+                }
+            });
+            Ok(())
+        }
+        "view_zoom_in" => cmd::zoom_in(app).map(|_| ()),
+        "view_zoom_out" => cmd::zoom_out(app).map(|_| ()),
+        "view_zoom_reset" => cmd::zoom_reset(app).map(|_| ()),
+        "view_clear_site_data" => cmd::confirm_clear_site_data(&app),
+        "view_toggle_devtools" => cmd::toggle_devtools(app, None),
+        "view_safe_mode" => cmd::relaunch(app, !setup::is_safe_mode()),
+        "view_always_on_top" => cmd::toggle_always_on_top(app).map(|_| ()),
+        "view_all_workspaces" => cmd::toggle_visible_on_all_workspaces(app).map(|_| ()),
+        "window_new" => {
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = cmd::new_window(app).await {
+                    error!("[menu:window_new] {}", e);
+                }
+            });
+            Ok(())
+        }
+        "window_snap_left" => cmd::snap_left(app),
+        "window_snap_right" => cmd::snap_right(app),
+        "window_snap_full" => cmd::snap_full(app),
+        "window_snap_center" => cmd::snap_center(app),
+        "quit" => cmd::quit(app),
+        _ => Ok(()),
+    };
+
+    if let Err(e) = result {
+        error!("[menu:{}] {}", event.id().as_ref(), e);
+    }
+}
+
+/// The focused chat window, `core` or one opened by `new_window`, falling back
+/// to `core` when another window like settings has focus.
+fn chat_window(app: &AppHandle) -> Result<Window, String> {
+    app.get_focused_window()
+        .filter(|win| {
+            win.get_webview(&setup::view_label(win.label(), "main"))
+                .is_some()
+        })
+        .or_else(|| app.get_window("core"))
+        .ok_or_else(|| "The core window does not exist".into())
+}
+
+/// Updates a check item in one of the top-level submenus, for state that can
+/// also be changed outside the menu.
+pub fn set_checked(app: &AppHandle, id: &str, checked: bool) -> tauri::Result<()> {
+    let Some(menu) = app.menu() else {
+        return Ok(());
+    };
+    for item in menu.items()? {
+        let check = item
+            .as_submenu()
+            .and_then(|submenu| submenu.get(id))
+            .and_then(|item| item.as_check_menuitem().cloned());
+        if let Some(check) = check {
+            return check.set_checked(checked);
+        }
+    }
+    Ok(())
+}
@@ -1,6 +1,23 @@
 pub mod cmd;
 pub mod conf;
 pub mod constant;
+pub mod crash;
+pub mod download;
+pub mod find;
+pub mod history;
+pub mod idle;
+pub mod logger;
+pub mod menu;
+pub mod model;
+pub mod navigation;
+pub mod notification;
+pub mod offline;
+pub mod platform;
 pub mod setup;
+pub mod shortcuts;
 pub mod template;
+pub mod tray;
+pub mod updater;
+pub mod upload;
+pub mod watcher;
 pub mod window;
@@ -0,0 +1,113 @@
+//! The model picked in ChatGPT's model switcher. `eval` can't return values, so
+//! the page answers through `model_reply`. The selectors live in
+//! `ChatAsk.SELECTORS` in ask.js.
+
+use log::{info, warn};
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+    },
+    time::Duration,
+};
+use tauri::{async_runtime::Sender, command, AppHandle, Manager, State};
+
+use crate::core::{conf::AppConf, constant::MODEL_REPLY_TIMEOUT, setup::lock};
+
+/// Requests waiting for the main view to answer, by id.
+#[derive(Default)]
+pub struct ModelReplies {
+    next_id: AtomicU64,
+    pending: Mutex<HashMap<u64, Sender<Result<String, String>>>>,
+}
+
+/// Selects `default_model` once a page has loaded, keeping the current model
+/// when it isn't offered there.
+pub fn apply_default(app: &AppHandle) {
+    let Some(name) = AppConf::load(app).ok().and_then(|conf| conf.default_model) else {
+        return;
+    };
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        match request(&app, "selectModel", Some(&name)).await {
+            Ok(model) => info!("[view:model] Selected the default model {}", model),
+            Err(e) => warn!("[view:model] Failed to select `{}`: {}", name, e),
+        }
+    });
+}
+
+/// Runs `ChatAsk.<method>(arg)` in the main view and waits for its result,
+/// rejections come back as the error.
+async fn request(app: &AppHandle, method: &str, arg: Option<&str>) -> Result<String, String> {
+    let replies = app.state::<ModelReplies>();
+    let id = replies.next_id.fetch_add(1, Ordering::SeqCst);
+    let (tx, mut rx) = tauri::async_runtime::channel(1);
+    lock(&replies.pending).insert(id, tx);
+
+    let script = format!(
+        r#"Promise.resolve()
+    .then(() => window.ChatAsk.{}({}))
+    .then((model) => ({{ model }}), (e) => ({{ error: String(e?.message ?? e) }}))
+    .then((reply) => window.__TAURI__.core.invoke('model_reply', {{ id: {}, ...reply }}));"#,
+        method,
+        arg.map(|arg| serde_json::Value::from(arg).to_string())
+            .unwrap_or_default(),
+        id
+    );
+    let result = match app.get_webview("main") {
+        Some(main) => main.eval(&script).map_err(|e| e.to_string()),
+        None => Err("The main webview does not exist".into()),
+    };
+    if let Err(e) = result {
+        lock(&replies.pending).remove(&id);
+        return Err(e);
+    }
+
+    let reply = tokio::time::timeout(Duration::from_secs(MODEL_REPLY_TIMEOUT), rx.recv()).await;
+    lock(&replies.pending).remove(&id);
+    match reply {
+        Ok(Some(result)) => result,
+        Ok(None) => Err("The request was dropped".into()),
+        Err(_) => Err("The page did not answer, is a chat open?".into()),
+    }
+}
+
+/// The label of the model switcher, e.g. `ChatGPT 4o`.
+#[command]
+pub async fn get_current_model(app: AppHandle) -> Result<String, String> {
+    request(&app, "currentModel", None)
+        .await
+        .map_err(|e| format!("[cmd:get_current_model] {}", e))
+}
+
+/// Opens the model switcher and picks the entry labelled `name`, ignoring case,
+/// returning its full label. Fails listing the offered models when none matches.
+#[command]
+pub async fn set_model(app: AppHandle, name: String) -> Result<String, String> {
+    if name.trim().is_empty() {
+        return Err("[cmd:set_model] The model name is empty".into());
+    }
+    request(&app, "selectModel", Some(&name))
+        .await
+        .map_err(|e| format!("[cmd:set_model] {}", e))
+}
+
+/// Answer to `request`, sent by the main view.
+#[command]
+pub fn model_reply(
+    replies: State<ModelReplies>,
+    id: u64,
+    model: Option<String>,
+    error: Option<String>,
+) {
+    let Some(tx) = lock(&replies.pending).remove(&id) else {
+        return;
+    };
+    let result = match (model, error) {
+        (_, Some(error)) => Err(error),
+        (Some(model), None) => Ok(model),
+        (None, None) => Err("The page returned no model".into()),
+    };
+    let _ = tx.try_send(result);
+}
@@ -0,0 +1,203 @@
+use log::error;
+use std::{collections::HashMap, sync::Mutex};
+use tauri::{command, AppHandle, Manager, Url, Window};
+use tauri_plugin_shell::ShellExt;
+
+use crate::core::{
+    conf::AppConf,
+    offline,
+    setup::{lock, view_label},
+};
+
+/// The main views' session histories as reported by `main-url-changed`, by
+/// window label, so back and forward can be kept on the start URL's origin.
+#[derive(Default)]
+pub struct MainHistory(Mutex<HashMap<String, Session>>);
+
+#[derive(Default)]
+struct Session {
+    urls: Vec<Url>,
+    index: usize,
+    /// Step taken by `go_back`/`go_forward`, completed by the next URL change.
+    pending: Option<isize>,
+}
+
+impl Session {
+    fn visit(&mut self, url: Url) {
+        if let Some(step) = self.pending.take() {
+            if let Some(index) = self.index.checked_add_signed(step) {
+                self.index = index.min(self.urls.len().saturating_sub(1));
+            }
+            // The webview knows better where it ended up, e.g. after a redirect
+            if let Some(entry) = self.urls.get_mut(self.index) {
+                *entry = url;
+            }
+            return;
+        }
+        // Page loads and in-page route changes both report the same URL
+        if self.urls.get(self.index) == Some(&url) {
+            return;
+        }
+        self.urls.truncate(self.index + 1);
+        self.urls.push(url);
+        self.index = self.urls.len() - 1;
+    }
+
+    fn target(&self, step: isize) -> Option<&Url> {
+        self.index
+            .checked_add_signed(step)
+            .and_then(|index| self.urls.get(index))
+    }
+}
+
+/// Records a URL change of the main view of `window`.
+pub fn visit(app: &AppHandle, window: &str, url: &Url) {
+    let history = app.state::<MainHistory>();
+    lock(&history.0)
+        .entry(window.to_string())
+        .or_default()
+        .visit(url.clone());
+}
+
+/// The core window's main view URL as last reported.
+pub fn current(app: &AppHandle) -> Option<Url> {
+    let history = app.state::<MainHistory>();
+    let sessions = lock(&history.0);
+    let session = sessions.get("core")?;
+    session.urls.get(session.index).cloned()
+}
+
+/// Forgets the core window's history, for when its main view is recreated.
+pub fn reset(app: &AppHandle) {
+    let history = app.state::<MainHistory>();
+    lock(&history.0).remove("core");
+}
+
+#[command]
+pub fn go_back(window: Window) -> Result<(), String> {
+    go(&window, -1, "window.history.back()").map_err(|e| format!("[cmd:go_back] {}", e))
+}
+
+#[command]
+pub fn go_forward(window: Window) -> Result<(), String> {
+    go(&window, 1, "window.history.forward()").map_err(|e| format!("[cmd:go_forward] {}", e))
+}
+
+#[command]
+pub fn can_go_back(window: Window) -> bool {
+    can_go_in(&window, -1)
+}
+
+#[command]
+pub fn can_go_forward(window: Window) -> bool {
+    can_go_in(&window, 1)
+}
+
+fn can_go_in(window: &Window, step: isize) -> bool {
+    let app = window.app_handle();
+    let history = app.state::<MainHistory>();
+    let sessions = lock(&history.0);
+    sessions
+        .get(window.label())
+        .is_some_and(|session| can_go(app, session, step))
+}
+
+/// Steps through the history of the main view of `window`, doing nothing when
+/// the entry is missing or on another origin than the start URL.
+fn go(window: &Window, step: isize, script: &str) -> Result<(), String> {
+    let app = window.app_handle();
+    let history = app.state::<MainHistory>();
+    let mut sessions = lock(&history.0);
+    let Some(session) = sessions.get_mut(window.label()) else {
+        return Ok(());
+    };
+    if !can_go(app, session, step) {
+        return Ok(());
+    }
+    window
+        .get_webview(&view_label(window.label(), "main"))
+        .ok_or("The main webview does not exist")?
+        .eval(script)
+        .map_err(|e| e.to_string())?;
+    session.pending = Some(step);
+    Ok(())
+}
+
+fn can_go(app: &AppHandle, session: &Session, step: isize) -> bool {
+    let Some(target) = session.target(step) else {
+        return false;
+    };
+    AppConf::load(app).is_ok_and(|conf| target.origin() == conf.start_url().origin())
+}
+
+/// Handles navigations of the main view. Links leaving the start URL's host are
+/// opened in the system browser instead, unless they match `allowed_hosts` or
+/// `auth_domains`.
+pub fn handle_navigation(app: &AppHandle, url: &Url) -> bool {
+    // about:blank, blob:, data: and friends never leave the app
+    if !matches!(url.scheme(), "http" | "https") {
+        return true;
+    }
+
+    // The bundled offline page is served over http on Windows and by the dev server
+    if offline::is_offline_page(app, url) {
+        return true;
+    }
+
+    let Ok(conf) = AppConf::load(app) else {
+        return true;
+    };
+    let Some(host) = url.host_str() else {
+        return true;
+    };
+
+    let start_url = conf.start_url();
+    let internal = start_url
+        .host_str()
+        .into_iter()
+        .chain(conf.allowed_hosts.iter().map(String::as_str))
+        .chain(conf.auth_domains.iter().map(String::as_str))
+        .any(|allowed| host_matches(host, allowed));
+    if internal {
+        return true;
+    }
+
+    if let Err(e) = app.shell().open(url.as_str(), None) {
+        error!("[view:navigation] Failed to open {} externally: {}", url, e);
+    }
+    false
+}
+
+/// Initialization script that turns `window.open` calls to `auth_domains` into
+/// a navigation of the same view. Once the provider redirects back to the start
+/// URL the app carries on as usual.
+pub fn auth_popup_script(conf: &AppConf) -> String {
+    let auth_domains = serde_json::to_string(&conf.auth_domains).unwrap_or_else(|_| "[]".into());
+    format!(
+        r#"(function () {{
+  const authDomains = {};
+  const isAuthHost = (host) =>
+    authDomains.some((domain) => host === domain || host.endsWith('.' + domain));
+  const open = window.open;
+  window.open = function (url, ...args) {{
+    try {{
+      const target = new URL(url, window.location.href);
+      if (isAuthHost(target.hostname)) {{
+        window.location.href = target.href;
+        return null;
+      }}
+    }} catch (_) {{}}
+    return open.call(window, url, ...args);
+  }};
+}})();"#,
+        auth_domains
+    )
+}
+
+/// Whether `host` is `allowed` or one of its subdomains.
+fn host_matches(host: &str, allowed: &str) -> bool {
+    host == allowed
+        || host
+            .strip_suffix(allowed)
+            .is_some_and(|prefix| prefix.ends_with('.'))
+}
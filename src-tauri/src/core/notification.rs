@@ -0,0 +1,65 @@
+//! Native notifications through `tauri-plugin-notification`. The plugin is
+//! behind the default `notification` cargo feature, without it nothing is shown.
+//!
+//! The plugin has no click callback on desktop. On macOS a click activates the
+//! app, elsewhere the window comes back through the tray or `toggle_window`, as
+//! the settings view says.
+
+use log::info;
+use tauri::{command, AppHandle, Manager};
+
+use crate::core::conf::AppConf;
+
+#[cfg(feature = "notification")]
+use {
+    crate::core::constant::DEFAULT_WINDOW_TITLE, log::error,
+    tauri_plugin_notification::NotificationExt,
+};
+
+#[cfg(feature = "notification")]
+pub fn init(app: &AppHandle) {
+    if let Err(e) = app.plugin(tauri_plugin_notification::init()) {
+        error!(
+            "[core:notification] Failed to register notifications: {}",
+            e
+        );
+    }
+}
+
+#[cfg(not(feature = "notification"))]
+pub fn init(_app: &AppHandle) {}
+
+#[cfg(feature = "notification")]
+fn show(app: &AppHandle, body: &str) {
+    let result = app
+        .notification()
+        .builder()
+        .title(DEFAULT_WINDOW_TITLE)
+        .body(body)
+        .show();
+    if let Err(e) = result {
+        error!("[core:notification] Failed to notify: {}", e);
+    }
+}
+
+#[cfg(not(feature = "notification"))]
+fn show(_app: &AppHandle, _body: &str) {
+    info!("[core:notification] This build has no notifications");
+}
+
+/// Reported by the main view once a response has finished streaming, see
+/// `RESPONSE_COMPLETE_SCRIPT`. Notifies with the start of the response when
+/// `notify_on_complete` is on and the core window is in the background.
+#[command]
+pub fn response_complete(app: AppHandle, preview: Option<String>) {
+    let in_front = app.get_window("core").is_some_and(|win| {
+        win.is_visible().unwrap_or(false)
+            && !win.is_minimized().unwrap_or(false)
+            && win.is_focused().unwrap_or(false)
+    });
+    if in_front || !AppConf::load(&app).is_ok_and(|conf| conf.notify_on_complete) {
+        return;
+    }
+    info!("[core:notification] Response completed in the background");
+    show(&app, preview.as_deref().unwrap_or("The response is ready"));
+}
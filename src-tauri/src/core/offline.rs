@@ -0,0 +1,111 @@
+use log::{error, warn};
+use std::{
+    net::TcpStream,
+    sync::atomic::{AtomicBool, Ordering},
+    time::Duration,
+};
+use tauri::{AppHandle, Manager, Url};
+
+use crate::core::{cmd, conf::AppConf, constant::OFFLINE_PROBE_TIMEOUT};
+
+/// Set while a background retry loop is running, so there is only ever one.
+static RETRYING: AtomicBool = AtomicBool::new(false);
+
+/// Probes the start URL's host when the main view starts loading one of its
+/// pages, and swaps in the bundled `offline.html` when it can't be reached.
+pub fn check(app: &AppHandle, url: &Url) {
+    let Ok(conf) = AppConf::load(app) else {
+        return;
+    };
+    if url.host_str().is_none() || url.host_str() != conf.start_url().host_str() {
+        return;
+    }
+
+    let app = app.clone();
+    let url = url.clone();
+    tauri::async_runtime::spawn(async move {
+        if reachable(url.clone()).await {
+            return;
+        }
+        warn!("[view:offline] {} is unreachable", url);
+        if let Err(e) = show_offline_page(&app) {
+            error!("[view:offline] {}", e);
+            return;
+        }
+        if conf.retry_interval_secs > 0 && !RETRYING.swap(true, Ordering::SeqCst) {
+            retry(&app, conf.start_url(), conf.retry_interval_secs).await;
+            RETRYING.store(false, Ordering::SeqCst);
+        }
+    });
+}
+
+/// URL of `offline.html`, served from the dev server or the bundled frontend.
+pub fn offline_url(app: &AppHandle) -> Url {
+    let base = if cfg!(dev) {
+        app.config().build.dev_url.clone()
+    } else {
+        None
+    };
+    let base = base.unwrap_or_else(|| {
+        // Same origins Tauri serves the bundled assets from
+        let origin = if cfg!(windows) {
+            "http://tauri.localhost"
+        } else {
+            "tauri://localhost"
+        };
+        Url::parse(origin).expect("valid app origin")
+    });
+    base.join("offline.html").expect("valid offline page url")
+}
+
+/// Whether `url` is the bundled offline page.
+pub fn is_offline_page(app: &AppHandle, url: &Url) -> bool {
+    let offline = offline_url(app);
+    url.origin() == offline.origin() && url.path() == offline.path()
+}
+
+fn show_offline_page(app: &AppHandle) -> Result<(), String> {
+    app.get_webview("main")
+        .ok_or("The main webview does not exist")?
+        .navigate(offline_url(app))
+        .map_err(|e| format!("Failed to show offline page: {}", e))
+}
+
+/// Polls the start URL in the background and reloads the main view once it is
+/// reachable again; stops early when the user navigates away from the offline page.
+async fn retry(app: &AppHandle, start_url: Url, interval_secs: u64) {
+    loop {
+        tokio::time::sleep(Duration::from_secs(interval_secs)).await;
+
+        let on_offline_page = app
+            .get_webview("main")
+            .and_then(|view| view.url().ok())
+            .is_some_and(|url| is_offline_page(app, &url));
+        if !on_offline_page {
+            return;
+        }
+
+        if reachable(start_url.clone()).await {
+            if let Err(e) = cmd::reload_main(app.clone()) {
+                error!("[view:offline] {}", e);
+            }
+            return;
+        }
+    }
+}
+
+/// Whether a TCP connection to the host of `url` can be opened.
+async fn reachable(url: Url) -> bool {
+    tauri::async_runtime::spawn_blocking(move || {
+        url.socket_addrs(|| None)
+            .ok()
+            .into_iter()
+            .flatten()
+            .any(|addr| {
+                TcpStream::connect_timeout(&addr, Duration::from_secs(OFFLINE_PROBE_TIMEOUT))
+                    .is_ok()
+            })
+    })
+    .await
+    .unwrap_or(true)
+}
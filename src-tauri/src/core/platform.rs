@@ -0,0 +1,926 @@
+//! Native window tweaks that Tauri doesn't expose yet.
+
+use log::error;
+use std::path::Path;
+use tauri::{PhysicalPosition, PhysicalSize, Webview, Window};
+
+use crate::core::cmd::ClearedSiteData;
+
+/// Sets the opacity of the whole window (titlebar and views alike) on the main
+/// thread, failures are logged since some platforms can't honor it.
+pub fn set_opacity(window: &Window, opacity: f64) -> tauri::Result<()> {
+    let win = window.clone();
+    window.run_on_main_thread(move || {
+        if let Err(e) = apply_opacity(&win, opacity) {
+            error!("[window:opacity] Failed to set opacity: {}", e);
+        }
+    })
+}
+
+#[cfg(target_os = "macos")]
+fn apply_opacity(window: &Window, opacity: f64) -> Result<(), Box<dyn std::error::Error>> {
+    use objc::{msg_send, runtime::Object, sel, sel_impl};
+
+    let ns_window = window.ns_window()? as *mut Object;
+    unsafe {
+        let _: () = msg_send![ns_window, setAlphaValue: opacity];
+    }
+    Ok(())
+}
+
+#[cfg(windows)]
+fn apply_opacity(window: &Window, opacity: f64) -> Result<(), Box<dyn std::error::Error>> {
+    use windows::Win32::{
+        Foundation::COLORREF,
+        UI::WindowsAndMessaging::{
+            GetWindowLongPtrW, SetLayeredWindowAttributes, SetWindowLongPtrW, GWL_EXSTYLE,
+            LWA_ALPHA, WS_EX_LAYERED,
+        },
+    };
+
+    let hwnd = window.hwnd()?;
+    unsafe {
+        let style = GetWindowLongPtrW(hwnd, GWL_EXSTYLE);
+        SetWindowLongPtrW(hwnd, GWL_EXSTYLE, style | WS_EX_LAYERED.0 as isize);
+        SetLayeredWindowAttributes(
+            hwnd,
+            COLORREF(0),
+            (opacity * 255.0).round() as u8,
+            LWA_ALPHA,
+        )?;
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+fn apply_opacity(window: &Window, opacity: f64) -> Result<(), Box<dyn std::error::Error>> {
+    use gtk::prelude::WidgetExt;
+
+    let gtk_window = window.gtk_window()?;
+    if opacity < 1.0
+        && !gtk_window
+            .screen()
+            .is_some_and(|screen| screen.is_composited())
+    {
+        log::warn!("[window:opacity] No compositing window manager, opacity is unsupported");
+    }
+    gtk_window.set_opacity(opacity);
+    Ok(())
+}
+
+#[cfg(not(any(target_os = "macos", windows, target_os = "linux")))]
+fn apply_opacity(_window: &Window, opacity: f64) -> Result<(), Box<dyn std::error::Error>> {
+    if opacity < 1.0 {
+        log::warn!("[window:opacity] Opacity is unsupported on this platform");
+    }
+    Ok(())
+}
+
+/// Sets the default webview background before any webview exists. WebView2
+/// only reads it when its environment is created, the other platforms use
+/// `set_background_color` once the views are attached.
+pub fn init_background_color((r, g, b): (u8, u8, u8)) {
+    if cfg!(windows) {
+        std::env::set_var(
+            "WEBVIEW2_DEFAULT_BACKGROUND_COLOR",
+            format!("FF{:02X}{:02X}{:02X}", r, g, b),
+        );
+    }
+}
+
+/// Paints `color` behind the window's webviews until their pages draw, so
+/// there is no white flash while loading.
+pub fn set_background_color(window: &Window, color: (u8, u8, u8)) -> tauri::Result<()> {
+    for view in window.webviews() {
+        view.with_webview(move |webview| {
+            if let Err(e) = apply_view_background(webview, color) {
+                error!("[window:background] Failed to set view background: {}", e);
+            }
+        })?;
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        let win = window.clone();
+        window.run_on_main_thread(move || {
+            if let Err(e) = apply_window_background(&win, color) {
+                error!("[window:background] Failed to set window background: {}", e);
+            }
+        })?;
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "macos")]
+fn apply_view_background(
+    webview: tauri::webview::PlatformWebview,
+    _color: (u8, u8, u8),
+) -> Result<(), Box<dyn std::error::Error>> {
+    use objc::{class, msg_send, runtime::Object, sel, sel_impl};
+
+    // WKWebView has no background color, it is made see-through to the window's
+    let webview = webview.inner();
+    unsafe {
+        let no: *mut Object = msg_send![class!(NSNumber), numberWithBool: false];
+        let key: *mut Object =
+            msg_send![class!(NSString), stringWithUTF8String: c"drawsBackground".as_ptr()];
+        let _: () = msg_send![webview, setValue: no forKey: key];
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "macos")]
+fn apply_window_background(
+    window: &Window,
+    (r, g, b): (u8, u8, u8),
+) -> Result<(), Box<dyn std::error::Error>> {
+    use objc::{class, msg_send, runtime::Object, sel, sel_impl};
+
+    let ns_window = window.ns_window()? as *mut Object;
+    unsafe {
+        let color: *mut Object = msg_send![class!(NSColor),
+            colorWithSRGBRed: r as f64 / 255.0
+            green: g as f64 / 255.0
+            blue: b as f64 / 255.0
+            alpha: 1.0f64];
+        let _: () = msg_send![ns_window, setBackgroundColor: color];
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+fn apply_view_background(
+    webview: tauri::webview::PlatformWebview,
+    (r, g, b): (u8, u8, u8),
+) -> Result<(), Box<dyn std::error::Error>> {
+    use webkit2gtk::WebViewExt;
+
+    webview.inner().set_background_color(&gtk::gdk::RGBA::new(
+        r as f64 / 255.0,
+        g as f64 / 255.0,
+        b as f64 / 255.0,
+        1.0,
+    ));
+    Ok(())
+}
+
+// WebView2 already got it from `init_background_color`
+#[cfg(not(any(target_os = "macos", target_os = "linux")))]
+fn apply_view_background(
+    _webview: tauri::webview::PlatformWebview,
+    _color: (u8, u8, u8),
+) -> Result<(), Box<dyn std::error::Error>> {
+    Ok(())
+}
+
+/// Puts an `NSVisualEffectView` material behind the window's webviews, so it
+/// shows through wherever a page leaves its background transparent, like the
+/// titlebar. Unknown materials keep the solid background. macOS only.
+#[cfg(target_os = "macos")]
+pub fn set_vibrancy(window: &Window, material: &str) -> tauri::Result<()> {
+    use tauri::window::{Effect, EffectState, EffectsBuilder};
+
+    let effect = match material {
+        "titlebar" => Effect::Titlebar,
+        "selection" => Effect::Selection,
+        "menu" => Effect::Menu,
+        "popover" => Effect::Popover,
+        "sidebar" => Effect::Sidebar,
+        "header_view" => Effect::HeaderView,
+        "sheet" => Effect::Sheet,
+        "window_background" => Effect::WindowBackground,
+        "hud_window" => Effect::HudWindow,
+        "full_screen_ui" => Effect::FullScreenUI,
+        "tooltip" => Effect::Tooltip,
+        "content_background" => Effect::ContentBackground,
+        "under_window_background" => Effect::UnderWindowBackground,
+        "under_page_background" => Effect::UnderPageBackground,
+        _ => {
+            log::warn!(
+                "[window:vibrancy] Unknown material `{}`, keeping the solid background",
+                material
+            );
+            return Ok(());
+        }
+    };
+    window.set_effects(
+        EffectsBuilder::new()
+            .effect(effect)
+            .state(EffectState::FollowsWindowActiveState)
+            .build(),
+    )
+}
+
+#[cfg(not(target_os = "macos"))]
+pub fn set_vibrancy(_window: &Window, _material: &str) -> tauri::Result<()> {
+    Ok(())
+}
+
+/// Rounds the window corners on the main thread. Windows 10 and older reject
+/// the attribute, which is logged and otherwise ignored.
+pub fn set_rounded_corners(window: &Window) -> tauri::Result<()> {
+    let win = window.clone();
+    window.run_on_main_thread(move || {
+        if let Err(e) = apply_rounded_corners(&win) {
+            log::info!("[window:corners] Rounded corners are unavailable: {}", e);
+        }
+    })
+}
+
+#[cfg(windows)]
+fn apply_rounded_corners(window: &Window) -> Result<(), Box<dyn std::error::Error>> {
+    use windows::Win32::Graphics::Dwm::{
+        DwmSetWindowAttribute, DWMWA_WINDOW_CORNER_PREFERENCE, DWMWCP_ROUND,
+        DWM_WINDOW_CORNER_PREFERENCE,
+    };
+
+    let hwnd = window.hwnd()?;
+    unsafe {
+        DwmSetWindowAttribute(
+            hwnd,
+            DWMWA_WINDOW_CORNER_PREFERENCE,
+            &DWMWCP_ROUND as *const _ as *const std::ffi::c_void,
+            std::mem::size_of::<DWM_WINDOW_CORNER_PREFERENCE>() as u32,
+        )?;
+    }
+    Ok(())
+}
+
+#[cfg(not(windows))]
+fn apply_rounded_corners(_window: &Window) -> Result<(), Box<dyn std::error::Error>> {
+    Err("Only supported on Windows 11".into())
+}
+
+/// Physical bounds of the monitor the window is on, minus the taskbar, dock and
+/// menu bar. Falls back to the whole monitor where the work area is unknown.
+pub fn work_area(window: &Window) -> Option<(PhysicalPosition<i32>, PhysicalSize<u32>)> {
+    match native_work_area(window) {
+        Ok(area) => Some(area),
+        Err(e) => {
+            log::warn!(
+                "[window:work_area] No work area, using the whole monitor: {}",
+                e
+            );
+            let monitor = window.current_monitor().ok().flatten()?;
+            Some((*monitor.position(), *monitor.size()))
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn native_work_area(
+    window: &Window,
+) -> Result<(PhysicalPosition<i32>, PhysicalSize<u32>), Box<dyn std::error::Error>> {
+    use objc::{class, msg_send, runtime::Object, sel, sel_impl};
+
+    #[repr(C)]
+    struct NSRect {
+        x: f64,
+        y: f64,
+        width: f64,
+        height: f64,
+    }
+
+    let ns_window = window.ns_window()? as *mut Object;
+    let scale_factor = window.scale_factor()?;
+    unsafe {
+        let screen: *mut Object = msg_send![ns_window, screen];
+        if screen.is_null() {
+            return Err("The window is not on a screen".into());
+        }
+        let visible: NSRect = msg_send![screen, visibleFrame];
+        // Cocoa counts from the bottom of the primary screen, Tauri from its top
+        let screens: *mut Object = msg_send![class!(NSScreen), screens];
+        let primary: *mut Object = msg_send![screens, objectAtIndex: 0usize];
+        let primary_frame: NSRect = msg_send![primary, frame];
+        let top = primary_frame.height - (visible.y + visible.height);
+        Ok((
+            PhysicalPosition::new(
+                (visible.x * scale_factor).round() as i32,
+                (top * scale_factor).round() as i32,
+            ),
+            PhysicalSize::new(
+                (visible.width * scale_factor).round() as u32,
+                (visible.height * scale_factor).round() as u32,
+            ),
+        ))
+    }
+}
+
+#[cfg(windows)]
+fn native_work_area(
+    window: &Window,
+) -> Result<(PhysicalPosition<i32>, PhysicalSize<u32>), Box<dyn std::error::Error>> {
+    use windows::Win32::Graphics::Gdi::{
+        GetMonitorInfoW, MonitorFromWindow, MONITORINFO, MONITOR_DEFAULTTONEAREST,
+    };
+
+    let hwnd = window.hwnd()?;
+    let mut info = MONITORINFO {
+        cbSize: std::mem::size_of::<MONITORINFO>() as u32,
+        ..Default::default()
+    };
+    unsafe {
+        let monitor = MonitorFromWindow(hwnd, MONITOR_DEFAULTTONEAREST);
+        GetMonitorInfoW(monitor, &mut info).ok()?;
+    }
+    let work = info.rcWork;
+    Ok((
+        PhysicalPosition::new(work.left, work.top),
+        PhysicalSize::new(
+            (work.right - work.left) as u32,
+            (work.bottom - work.top) as u32,
+        ),
+    ))
+}
+
+#[cfg(target_os = "linux")]
+fn native_work_area(
+    window: &Window,
+) -> Result<(PhysicalPosition<i32>, PhysicalSize<u32>), Box<dyn std::error::Error>> {
+    use gtk::{gdk::prelude::MonitorExt, prelude::WidgetExt};
+
+    let gdk_window = window
+        .gtk_window()?
+        .window()
+        .ok_or("The window is not realized")?;
+    let monitor = gdk_window
+        .display()
+        .monitor_at_window(&gdk_window)
+        .ok_or("The window is not on a monitor")?;
+    // GDK works in scaled pixels
+    let scale = monitor.scale_factor();
+    let area = monitor.workarea();
+    Ok((
+        PhysicalPosition::new(area.x() * scale, area.y() * scale),
+        PhysicalSize::new(
+            (area.width() * scale) as u32,
+            (area.height() * scale) as u32,
+        ),
+    ))
+}
+
+#[cfg(not(any(target_os = "macos", windows, target_os = "linux")))]
+fn native_work_area(
+    _window: &Window,
+) -> Result<(PhysicalPosition<i32>, PhysicalSize<u32>), Box<dyn std::error::Error>> {
+    Err("Work areas are unsupported on this platform".into())
+}
+
+/// Opens the platform file manager with `path` selected.
+pub fn reveal_in_file_manager(path: &Path) -> std::io::Result<()> {
+    use std::process::Command;
+
+    if cfg!(target_os = "macos") {
+        Command::new("open").arg("-R").arg(path).spawn()?;
+    } else if cfg!(windows) {
+        // explorer exits with 1 even when it worked, so only spawning is checked
+        let mut select = std::ffi::OsString::from("/select,");
+        select.push(path);
+        Command::new("explorer").arg(select).spawn()?;
+    } else {
+        // The freedesktop FileManager1 interface, implemented by most file managers
+        let uri = tauri::Url::from_file_path(path).map_err(|_| {
+            std::io::Error::new(std::io::ErrorKind::InvalidInput, "not an absolute path")
+        })?;
+        let status = Command::new("dbus-send")
+            .args([
+                "--session",
+                "--dest=org.freedesktop.FileManager1",
+                "--type=method_call",
+                "/org/freedesktop/FileManager1",
+                "org.freedesktop.FileManager1.ShowItems",
+            ])
+            .arg(format!("array:string:{}", uri))
+            .arg("string:")
+            .status()?;
+        if !status.success() {
+            return Err(std::io::Error::other(format!(
+                "dbus-send exited with {}",
+                status
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Clears cookies, storage and cache of `webview` through its live engine, other
+/// views sharing the engine keep running. `profile` is the view's profile.
+#[cfg(target_os = "macos")]
+pub async fn clear_website_data(webview: &Webview, profile: &str) -> ClearedSiteData {
+    use block::ConcreteBlock;
+    use objc::{class, msg_send, runtime::Object, sel, sel_impl};
+
+    let (tx, mut rx) = tauri::async_runtime::channel(1);
+    let scheduled = webview.with_webview(move |webview| unsafe {
+        let config: *mut Object = msg_send![webview.inner(), configuration];
+        let store: *mut Object = msg_send![config, websiteDataStore];
+        let types: *mut Object = msg_send![class!(WKWebsiteDataStore), allWebsiteDataTypes];
+        let since: *mut Object = msg_send![class!(NSDate), distantPast];
+        let done = ConcreteBlock::new(move || {
+            let _ = tx.try_send(());
+        })
+        .copy();
+        let _: () = msg_send![store, removeDataOfTypes: types modifiedSince: since completionHandler: &*done];
+    });
+    let cleared = scheduled.is_ok() && rx.recv().await.is_some();
+    // wry gives every persistent WKWebView the default store, whatever the profile
+    let note = (profile != crate::core::constant::DEFAULT_PROFILE).then(|| {
+        format!(
+            "macOS keeps one store for all profiles, the data of every profile was cleared, not just `{}`",
+            profile
+        )
+    });
+    ClearedSiteData {
+        confirmed: true,
+        cookies: cleared,
+        storage: cleared,
+        cache: cleared,
+        note,
+    }
+}
+
+#[cfg(windows)]
+pub async fn clear_website_data(webview: &Webview, _profile: &str) -> ClearedSiteData {
+    use webview2_com::{
+        ClearBrowsingDataCompletedHandler,
+        Microsoft::Web::WebView2::Win32::{ICoreWebView2Profile2, ICoreWebView2_13},
+    };
+    use windows::core::Interface;
+
+    let (tx, mut rx) = tauri::async_runtime::channel(1);
+    let scheduled = webview.with_webview(move |webview| {
+        let handler_tx = tx.clone();
+        let handler = ClearBrowsingDataCompletedHandler::create(Box::new(move |result| {
+            let _ = handler_tx.try_send(result.map_err(|e| e.to_string()));
+            Ok(())
+        }));
+        // Profiles came with ICoreWebView2_13, older runtimes lack them
+        let result = unsafe {
+            webview
+                .controller()
+                .CoreWebView2()
+                .and_then(|core| core.cast::<ICoreWebView2_13>())
+                .and_then(|core| core.Profile())
+                .and_then(|profile| profile.cast::<ICoreWebView2Profile2>())
+                .and_then(|profile| profile.ClearBrowsingDataAll(&handler))
+        };
+        if let Err(e) = result {
+            let _ = tx.try_send(Err(e.to_string()));
+        }
+    });
+    let cleared = match scheduled {
+        Ok(()) => rx
+            .recv()
+            .await
+            .unwrap_or_else(|| Err("The data was never cleared".into())),
+        Err(e) => Err(e.to_string()),
+    };
+    if let Err(e) = &cleared {
+        error!("[view:main] Failed to clear site data: {}", e);
+    }
+    let cleared = cleared.is_ok();
+    ClearedSiteData {
+        confirmed: true,
+        cookies: cleared,
+        storage: cleared,
+        cache: cleared,
+        note: None,
+    }
+}
+
+#[cfg(target_os = "linux")]
+pub async fn clear_website_data(webview: &Webview, _profile: &str) -> ClearedSiteData {
+    use webkit2gtk::{
+        gio::Cancellable, glib::TimeSpan, WebContextExt, WebViewExt, WebsiteDataManagerExtManual,
+        WebsiteDataTypes,
+    };
+
+    // The view's web context is shared with the other views of its data dir, so
+    // its data manager also covers the cookie jar they hold in memory
+    let (tx, mut rx) = tauri::async_runtime::channel(1);
+    let scheduled = webview.with_webview(move |webview| {
+        let manager = webview
+            .inner()
+            .context()
+            .and_then(|context| context.website_data_manager());
+        let Some(manager) = manager else {
+            let _ = tx.try_send(Err("The webview has no website data manager".to_string()));
+            return;
+        };
+        manager.clear(
+            WebsiteDataTypes::ALL,
+            TimeSpan::from_seconds(0),
+            None::<&Cancellable>,
+            move |result| {
+                let _ = tx.try_send(result.map_err(|e| e.to_string()));
+            },
+        );
+    });
+    let cleared = match scheduled {
+        Ok(()) => rx
+            .recv()
+            .await
+            .unwrap_or_else(|| Err("The data was never cleared".into())),
+        Err(e) => Err(e.to_string()),
+    };
+    if let Err(e) = &cleared {
+        error!("[view:main] Failed to clear site data: {}", e);
+    }
+    let cleared = cleared.is_ok();
+    ClearedSiteData {
+        confirmed: true,
+        cookies: cleared,
+        storage: cleared,
+        cache: cleared,
+        note: None,
+    }
+}
+
+#[cfg(not(any(target_os = "macos", windows, target_os = "linux")))]
+pub async fn clear_website_data(_webview: &Webview, _profile: &str) -> ClearedSiteData {
+    log::warn!("[view:main] Clearing site data is unsupported on this platform");
+    ClearedSiteData {
+        confirmed: true,
+        ..Default::default()
+    }
+}
+
+/// Renders the webview's visible content to PNG bytes.
+#[cfg(target_os = "macos")]
+pub async fn capture_png(webview: &Webview) -> Result<Vec<u8>, String> {
+    use block::ConcreteBlock;
+    use objc::{msg_send, runtime::Object, sel, sel_impl};
+
+    let (tx, mut rx) = tauri::async_runtime::channel(1);
+    webview
+        .with_webview(move |webview| unsafe {
+            let done = ConcreteBlock::new(move |image: *mut Object, _error: *mut Object| {
+                let png = if image.is_null() {
+                    Err("WKWebView returned no snapshot".to_string())
+                } else {
+                    png_representation(image)
+                };
+                let _ = tx.try_send(png);
+            })
+            .copy();
+            let configuration: *mut Object = std::ptr::null_mut();
+            let _: () = msg_send![webview.inner(), takeSnapshotWithConfiguration: configuration completionHandler: &*done];
+        })
+        .map_err(|e| e.to_string())?;
+    rx.recv()
+        .await
+        .unwrap_or_else(|| Err("The snapshot was never taken".into()))
+}
+
+/// Encodes an `NSImage` as PNG.
+#[cfg(target_os = "macos")]
+unsafe fn png_representation(image: *mut objc::runtime::Object) -> Result<Vec<u8>, String> {
+    use objc::{class, msg_send, runtime::Object, sel, sel_impl};
+
+    // NSBitmapImageFileTypePNG
+    const PNG: usize = 4;
+    let tiff: *mut Object = msg_send![image, TIFFRepresentation];
+    let bitmap: *mut Object = msg_send![class!(NSBitmapImageRep), imageRepWithData: tiff];
+    if bitmap.is_null() {
+        return Err("The snapshot could not be converted".into());
+    }
+    let properties: *mut Object = msg_send![class!(NSDictionary), dictionary];
+    let data: *mut Object = msg_send![bitmap, representationUsingType: PNG properties: properties];
+    if data.is_null() {
+        return Err("The snapshot could not be encoded as PNG".into());
+    }
+    let length: usize = msg_send![data, length];
+    let bytes: *const u8 = msg_send![data, bytes];
+    Ok(std::slice::from_raw_parts(bytes, length).to_vec())
+}
+
+#[cfg(windows)]
+pub async fn capture_png(webview: &Webview) -> Result<Vec<u8>, String> {
+    use webview2_com::{
+        CapturePreviewCompletedHandler,
+        Microsoft::Web::WebView2::Win32::COREWEBVIEW2_CAPTURE_PREVIEW_IMAGE_FORMAT_PNG,
+    };
+    use windows::Win32::{
+        Foundation::HGLOBAL, System::Com::StructuredStorage::CreateStreamOnHGlobal,
+    };
+
+    let (tx, mut rx) = tauri::async_runtime::channel(1);
+    webview
+        .with_webview(move |webview| {
+            let handler_tx = tx.clone();
+            let result = unsafe {
+                CreateStreamOnHGlobal(HGLOBAL::default(), true).and_then(|stream| {
+                    let handler_stream = stream.clone();
+                    let handler = CapturePreviewCompletedHandler::create(Box::new(move |result| {
+                        let png = result.and_then(|_| read_stream(&handler_stream));
+                        let _ = handler_tx.try_send(png.map_err(|e| e.to_string()));
+                        Ok(())
+                    }));
+                    webview.controller().CoreWebView2()?.CapturePreview(
+                        COREWEBVIEW2_CAPTURE_PREVIEW_IMAGE_FORMAT_PNG,
+                        &stream,
+                        &handler,
+                    )
+                })
+            };
+            if let Err(e) = result {
+                let _ = tx.try_send(Err(e.to_string()));
+            }
+        })
+        .map_err(|e| e.to_string())?;
+    rx.recv()
+        .await
+        .unwrap_or_else(|| Err("The snapshot was never taken".into()))
+}
+
+/// Reads a whole in-memory stream from the start.
+#[cfg(windows)]
+unsafe fn read_stream(
+    stream: &windows::Win32::System::Com::IStream,
+) -> windows::core::Result<Vec<u8>> {
+    use windows::Win32::System::Com::{STATFLAG_NONAME, STATSTG, STREAM_SEEK_SET};
+
+    let mut stat = STATSTG::default();
+    stream.Stat(&mut stat, STATFLAG_NONAME)?;
+    stream.Seek(0, STREAM_SEEK_SET, None)?;
+    let mut buffer = vec![0u8; stat.cbSize as usize];
+    let mut read = 0;
+    stream
+        .Read(
+            buffer.as_mut_ptr().cast(),
+            buffer.len() as u32,
+            Some(&mut read),
+        )
+        .ok()?;
+    buffer.truncate(read as usize);
+    Ok(buffer)
+}
+
+#[cfg(target_os = "linux")]
+pub async fn capture_png(webview: &Webview) -> Result<Vec<u8>, String> {
+    use webkit2gtk::{gio::Cancellable, SnapshotOptions, SnapshotRegion, WebViewExt};
+
+    let (tx, mut rx) = tauri::async_runtime::channel(1);
+    webview
+        .with_webview(move |webview| {
+            webview.inner().snapshot(
+                SnapshotRegion::Visible,
+                SnapshotOptions::NONE,
+                None::<&Cancellable>,
+                move |result| {
+                    let png = result.map_err(|e| e.to_string()).and_then(|surface| {
+                        let mut png = Vec::new();
+                        surface
+                            .write_to_png(&mut png)
+                            .map(|_| png)
+                            .map_err(|e| e.to_string())
+                    });
+                    let _ = tx.try_send(png);
+                },
+            );
+        })
+        .map_err(|e| e.to_string())?;
+    rx.recv()
+        .await
+        .unwrap_or_else(|| Err("The snapshot was never taken".into()))
+}
+
+#[cfg(not(any(target_os = "macos", windows, target_os = "linux")))]
+pub async fn capture_png(_webview: &Webview) -> Result<Vec<u8>, String> {
+    Err("Screenshots are unsupported on this platform".into())
+}
+
+/// Prints the whole page of the webview to a PDF at `path`.
+#[cfg(target_os = "macos")]
+pub async fn print_pdf(webview: &Webview, path: &Path) -> Result<(), String> {
+    use block::ConcreteBlock;
+    use objc::{msg_send, runtime::Object, sel, sel_impl};
+
+    let (tx, mut rx) = tauri::async_runtime::channel(1);
+    webview
+        .with_webview(move |webview| unsafe {
+            let done = ConcreteBlock::new(move |data: *mut Object, _error: *mut Object| {
+                let pdf = if data.is_null() {
+                    Err("WKWebView returned no PDF".to_string())
+                } else {
+                    let length: usize = msg_send![data, length];
+                    let bytes: *const u8 = msg_send![data, bytes];
+                    Ok(std::slice::from_raw_parts(bytes, length).to_vec())
+                };
+                let _ = tx.try_send(pdf);
+            })
+            .copy();
+            // Needs macOS 11, a nil configuration covers the whole page
+            let configuration: *mut Object = std::ptr::null_mut();
+            let _: () = msg_send![webview.inner(), createPDFWithConfiguration: configuration completionHandler: &*done];
+        })
+        .map_err(|e| e.to_string())?;
+    let pdf = rx
+        .recv()
+        .await
+        .unwrap_or_else(|| Err("The PDF was never created".into()))?;
+    std::fs::write(path, pdf).map_err(|e| e.to_string())
+}
+
+#[cfg(windows)]
+pub async fn print_pdf(webview: &Webview, path: &Path) -> Result<(), String> {
+    use webview2_com::{
+        Microsoft::Web::WebView2::Win32::{ICoreWebView2PrintSettings, ICoreWebView2_7},
+        PrintToPdfCompletedHandler,
+    };
+    use windows::core::{Interface, HSTRING};
+
+    let path = HSTRING::from(path);
+    let (tx, mut rx) = tauri::async_runtime::channel(1);
+    webview
+        .with_webview(move |webview| {
+            let handler_tx = tx.clone();
+            let handler = PrintToPdfCompletedHandler::create(Box::new(move |result, success| {
+                let written = match result {
+                    Ok(()) if success => Ok(()),
+                    Ok(()) => Err("WebView2 failed to print the page".to_string()),
+                    Err(e) => Err(e.to_string()),
+                };
+                let _ = handler_tx.try_send(written);
+                Ok(())
+            }));
+            // PrintToPdf came with ICoreWebView2_7, older runtimes lack it
+            let result = unsafe {
+                webview
+                    .controller()
+                    .CoreWebView2()
+                    .and_then(|core| core.cast::<ICoreWebView2_7>())
+                    .and_then(|core| {
+                        core.PrintToPdf(&path, None::<&ICoreWebView2PrintSettings>, &handler)
+                    })
+            };
+            if let Err(e) = result {
+                let _ = tx.try_send(Err(e.to_string()));
+            }
+        })
+        .map_err(|e| e.to_string())?;
+    rx.recv()
+        .await
+        .unwrap_or_else(|| Err("The PDF was never printed".into()))
+}
+
+#[cfg(target_os = "linux")]
+pub async fn print_pdf(webview: &Webview, path: &Path) -> Result<(), String> {
+    use gtk::PrintSettings;
+    use webkit2gtk::{PrintOperation, PrintOperationExt};
+
+    let uri = tauri::Url::from_file_path(path)
+        .map_err(|_| format!("{} is not an absolute path", path.display()))?;
+    let (tx, mut rx) = tauri::async_runtime::channel(1);
+    webview
+        .with_webview(move |webview| {
+            // The same settings the GTK print dialog's "Print to File" uses
+            let settings = PrintSettings::new();
+            settings.set_printer("Print to File");
+            settings.set("output-file-format", Some("pdf"));
+            settings.set("output-uri", Some(uri.as_str()));
+
+            let operation = PrintOperation::new(&webview.inner());
+            operation.set_print_settings(&settings);
+            let failed_tx = tx.clone();
+            operation.connect_failed(move |_, e| {
+                let _ = failed_tx.try_send(Err(e.to_string()));
+            });
+            // Also emitted after `failed`, that result is sent first
+            operation.connect_finished(move |_| {
+                let _ = tx.try_send(Ok(()));
+            });
+            operation.print();
+        })
+        .map_err(|e| e.to_string())?;
+    rx.recv()
+        .await
+        .unwrap_or_else(|| Err("The PDF was never printed".into()))
+}
+
+#[cfg(not(any(target_os = "macos", windows, target_os = "linux")))]
+pub async fn print_pdf(_webview: &Webview, _path: &Path) -> Result<(), String> {
+    Err("PDF export is unsupported on this platform".into())
+}
+
+/// Calls `handler` with a reason whenever the webview's content process dies.
+#[cfg(target_os = "macos")]
+pub fn on_webview_crash<F: Fn(String) + Send + 'static>(
+    webview: &Webview,
+    handler: F,
+) -> tauri::Result<()> {
+    use objc::{
+        msg_send,
+        runtime::{class_addMethod, object_getClass, Class, Object, Sel},
+        sel, sel_impl,
+    };
+    use std::sync::atomic::Ordering;
+
+    // wry's navigation delegate doesn't implement the callback, so it is added to
+    // its class. Every webview shares that class, hence the pointer check.
+    extern "C" fn did_terminate(_this: &Object, _cmd: Sel, webview: *mut Object) {
+        if webview as usize == CRASH_WATCHED_VIEW.load(Ordering::SeqCst) {
+            if let Some(handler) = crate::core::setup::lock(&CRASH_HANDLER).as_ref() {
+                handler("web content process terminated".into());
+            }
+        }
+    }
+
+    *crate::core::setup::lock(&CRASH_HANDLER) = Some(Box::new(handler));
+    webview.with_webview(|webview| unsafe {
+        let view = webview.inner();
+        CRASH_WATCHED_VIEW.store(view as usize, Ordering::SeqCst);
+        let delegate: *mut Object = msg_send![view, navigationDelegate];
+        if delegate.is_null() {
+            error!("[view:crash] The webview has no navigation delegate");
+            return;
+        }
+        // Fails harmlessly when a previous view already added it
+        class_addMethod(
+            object_getClass(delegate) as *mut Class,
+            sel!(webViewWebContentProcessDidTerminate:),
+            std::mem::transmute::<extern "C" fn(&Object, Sel, *mut Object), objc::runtime::Imp>(
+                did_terminate,
+            ),
+            c"v@:@".as_ptr(),
+        );
+    })
+}
+
+#[cfg(target_os = "macos")]
+static CRASH_WATCHED_VIEW: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+#[cfg(target_os = "macos")]
+type CrashHandler = Box<dyn Fn(String) + Send>;
+#[cfg(target_os = "macos")]
+static CRASH_HANDLER: std::sync::Mutex<Option<CrashHandler>> = std::sync::Mutex::new(None);
+
+#[cfg(windows)]
+pub fn on_webview_crash<F: Fn(String) + Send + 'static>(
+    webview: &Webview,
+    handler: F,
+) -> tauri::Result<()> {
+    use webview2_com::{
+        Microsoft::Web::WebView2::Win32::{
+            COREWEBVIEW2_PROCESS_FAILED_KIND,
+            COREWEBVIEW2_PROCESS_FAILED_KIND_BROWSER_PROCESS_EXITED,
+            COREWEBVIEW2_PROCESS_FAILED_KIND_RENDER_PROCESS_EXITED,
+            COREWEBVIEW2_PROCESS_FAILED_KIND_RENDER_PROCESS_UNRESPONSIVE,
+        },
+        ProcessFailedEventHandler,
+    };
+    use windows::Win32::System::WinRT::EventRegistrationToken;
+
+    webview.with_webview(move |webview| {
+        let failed = ProcessFailedEventHandler::create(Box::new(move |_, args| {
+            let mut kind = COREWEBVIEW2_PROCESS_FAILED_KIND::default();
+            if let Some(args) = args {
+                unsafe { args.ProcessFailedKind(&mut kind)? };
+            }
+            // GPU and utility processes are restarted by WebView2 on its own
+            let reason = match kind {
+                COREWEBVIEW2_PROCESS_FAILED_KIND_RENDER_PROCESS_EXITED => "render process exited",
+                COREWEBVIEW2_PROCESS_FAILED_KIND_RENDER_PROCESS_UNRESPONSIVE => {
+                    "render process unresponsive"
+                }
+                COREWEBVIEW2_PROCESS_FAILED_KIND_BROWSER_PROCESS_EXITED => "browser process exited",
+                _ => return Ok(()),
+            };
+            handler(reason.into());
+            Ok(())
+        }));
+        let mut token = EventRegistrationToken::default();
+        let result = unsafe {
+            webview
+                .controller()
+                .CoreWebView2()
+                .and_then(|core| core.add_ProcessFailed(&failed, &mut token))
+        };
+        if let Err(e) = result {
+            error!("[view:crash] Failed to watch the webview: {}", e);
+        }
+    })
+}
+
+#[cfg(target_os = "linux")]
+pub fn on_webview_crash<F: Fn(String) + Send + 'static>(
+    webview: &Webview,
+    handler: F,
+) -> tauri::Result<()> {
+    use webkit2gtk::WebViewExt;
+
+    webview.with_webview(move |webview| {
+        webview
+            .inner()
+            .connect_web_process_terminated(move |_, reason| handler(format!("{:?}", reason)));
+    })
+}
+
+#[cfg(not(any(target_os = "macos", windows, target_os = "linux")))]
+pub fn on_webview_crash<F: Fn(String) + Send + 'static>(
+    _webview: &Webview,
+    _handler: F,
+) -> tauri::Result<()> {
+    log::warn!("[view:crash] Crash detection is unsupported on this platform");
+    Ok(())
+}
@@ -1,258 +1,1191 @@
+use log::{error, info, warn};
 use std::{
-    path::PathBuf,
-    sync::{Arc, Mutex},
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc, Mutex, MutexGuard, PoisonError,
+    },
+    time::{Duration, Instant},
 };
 use tauri::{
-    webview::DownloadEvent, App, LogicalPosition, Manager, PhysicalSize, WebviewBuilder,
-    WebviewUrl, WindowBuilder, WindowEvent,
+    ipc::CapabilityBuilder, webview::PageLoadEvent, App, AppHandle, DragDropEvent, Emitter,
+    Listener, LogicalPosition, Manager, PhysicalSize, RunEvent, Url, Webview, WebviewBuilder,
+    WebviewEvent, WebviewUrl, Window, WindowBuilder, WindowEvent, Wry,
 };
-use tauri_plugin_shell::ShellExt;
+
+use tauri_plugin_dialog::{DialogExt, MessageDialogKind};
 
 #[cfg(target_os = "macos")]
 use tauri::TitleBarStyle;
 
 use crate::core::{
-    conf::AppConf,
-    constant::{ASK_HEIGHT, INIT_SCRIPT, TITLEBAR_HEIGHT},
-    template,
+    cmd,
+    conf::{AppConf, ReduceMotion, TitlebarPosition},
+    constant::{
+        COLLAPSE_SIDEBAR_SCRIPT, DEFAULT_START_URL, DEFAULT_WINDOW_TITLE, FOCUS_COMPOSER_DELAY,
+        GEOMETRY_SAVE_DELAY, IDLE_ACTIVITY_SCRIPT, INIT_SCRIPT, REDUCE_MOTION_CSS,
+        RESIZE_LAYOUT_INTERVAL, RESPONSE_COMPLETE_SCRIPT, SAFE_MODE_FLAG, SPELLCHECK_SCRIPT,
+        WINDOW_HEIGHT, WINDOW_SETTINGS, WINDOW_WIDTH,
+    },
+    crash,
+    download::{handle_download_event, PendingDownloads},
+    history, idle, logger, menu, model,
+    navigation::{self, auth_popup_script, handle_navigation},
+    notification, offline, platform, shortcuts, template, tray, updater, upload, watcher,
 };
 
+/// Set from `SAFE_MODE_FLAG` on startup.
+static SAFE_MODE: AtomicBool = AtomicBool::new(false);
+
+/// Whether the app was launched with `SAFE_MODE_FLAG`.
+pub fn is_safe_mode() -> bool {
+    SAFE_MODE.load(Ordering::SeqCst)
+}
+
+/// The ACL checks every plugin command, like `plugin:event|emit`, against the
+/// remote URLs of `capabilities/desktop.json`. A custom `start_url` gets the
+/// same event and webview permissions for its origin.
+pub fn allow_start_url(app: &AppHandle, conf: &AppConf) {
+    let origin = conf.start_url().origin();
+    if origin == DEFAULT_START_URL.parse::<Url>().unwrap().origin() {
+        return;
+    }
+    let capability = CapabilityBuilder::new("start-url-capability")
+        .remote(format!("{}/*", origin.ascii_serialization()))
+        .local(false)
+        .window("*")
+        .permission("event:default")
+        .permission("event:allow-emit")
+        .permission("event:allow-emit-to")
+        .permission("webview:default")
+        .permission("webview:allow-set-webview-focus")
+        .permission("window:default");
+    match app.add_capability(capability) {
+        Ok(()) => info!(
+            "[core:setup] Allowed {} to emit events",
+            origin.ascii_serialization()
+        ),
+        Err(e) => error!("[core:setup] Failed to allow {}: {}", conf.start_url, e),
+    }
+}
+
 pub fn init(app: &mut App) -> Result<(), Box<dyn std::error::Error>> {
     let handle = app.handle();
+    SAFE_MODE.store(
+        std::env::args().any(|arg| arg == SAFE_MODE_FLAG),
+        Ordering::SeqCst,
+    );
+
+    let conf = &match AppConf::load(handle) {
+        Ok(conf) => conf,
+        Err(e) => {
+            logger::init(handle, Default::default());
+            // Returning the error would only panic, leave the event loop running for the dialog
+            fatal_error(handle, "Failed to load the configuration", e);
+            return Ok(());
+        }
+    };
+    logger::init(handle, conf.log_level);
+    // Logged so reports show where state lives, and any fallback from an unwritable folder
+    match AppConf::get_data_dir(handle) {
+        Ok(dir) => info!("[conf:data_dir] Using {}", dir.display()),
+        Err(e) => error!("[conf:data_dir] {}", e),
+    }
+    if is_safe_mode() {
+        warn!("[core:setup] Safe mode, scripts and custom.css are not loaded");
+    }
+    allow_start_url(handle, conf);
+
+    if cfg!(windows) && conf.visible_on_all_workspaces {
+        warn!("[core:window] visible_on_all_workspaces is unsupported on Windows, ignoring it");
+    }
+
+    match AppConf::get_scripts_path(handle) {
+        Ok(dir) => {
+            template::Template::new(dir);
+        }
+        Err(e) => error!("[scripts:template] Failed to locate scripts folder: {}", e),
+    }
+    platform::init_background_color(conf.background_color(AppConf::get_theme(handle)));
+
+    handle.on_menu_event(menu::handle_menu_event);
+    history::init(handle);
+    watch_main_url(handle);
+
+    if conf.first_run {
+        watch_first_run(handle);
+    }
+    idle::reset(handle);
 
-    let conf = &AppConf::load(handle)?;
-    let ask_mode_height = if conf.ask_mode { ASK_HEIGHT } else { 0.0 };
+    updater::init(handle, conf.auto_update);
+    notification::init(handle);
 
-    template::Template::new(AppConf::get_scripts_path(handle)?);
+    if conf.enable_tray {
+        if let Err(e) = tray::init(handle) {
+            error!("[core:tray] Failed to create tray icon: {}", e);
+        }
+    }
+
+    if conf.watch_scripts && !is_safe_mode() {
+        if let Err(e) = watcher::init(handle) {
+            error!("[scripts:watch] Failed to watch scripts: {}", e);
+        }
+    }
+
+    shortcuts::init(handle, conf);
 
     tauri::async_runtime::spawn({
         let handle = handle.clone();
         async move {
-            let mut core_window = WindowBuilder::new(&handle, "core").title("ChatGPT");
-
-            #[cfg(target_os = "macos")]
-            {
-                core_window = core_window
-                    .title_bar_style(TitleBarStyle::Overlay)
-                    .hidden_title(true);
+            if let Err(e) = create_main_window(&handle, "core") {
+                fatal_error(&handle, "Failed to open the main window", e);
             }
+        }
+    });
 
-            core_window = core_window
-                .resizable(true)
-                .inner_size(800.0, 600.0)
-                .min_inner_size(300.0, 200.0)
-                .theme(Some(AppConf::get_theme(&handle)));
-
-            let core_window = core_window
-                .build()
-                .expect("[core:window] Failed to build window");
-
-            let win_size = core_window
-                .inner_size()
-                .expect("[core:window] Failed to get window size");
-            // Wrap the window in Arc<Mutex<_>> to manage ownership across threads
-            let window = Arc::new(Mutex::new(core_window));
-
-            let main_view =
-                WebviewBuilder::new("main", WebviewUrl::App("https://chatgpt.com".into()))
-                    .auto_resize()
-                    .on_download({
-                        let app_handle = handle.clone();
-                        let download_path = Arc::new(Mutex::new(PathBuf::new()));
-                        move |_, event| {
-                            match event {
-                                DownloadEvent::Requested { destination, .. } => {
-                                    let download_dir = app_handle
-                                        .path()
-                                        .download_dir()
-                                        .expect("[view:download] Failed to get download directory");
-                                    let mut locked_path = download_path
-                                        .lock()
-                                        .expect("[view:download] Failed to lock download path");
-                                    *locked_path = download_dir.join(&destination);
-                                    *destination = locked_path.clone();
-                                }
-                                DownloadEvent::Finished { success, .. } => {
-                                    let final_path = download_path
-                                        .lock()
-                                        .expect("[view:download] Failed to lock download path")
-                                        .clone();
-
-                                    if success {
-                                        app_handle
-                                            .shell()
-                                            .open(final_path.to_string_lossy(), None)
-                                            .expect("[view:download] Failed to open file");
-                                    }
-                                }
-                                _ => (),
-                            }
-                            true
-                        }
-                    })
-                    .initialization_script(&AppConf::load_script(&handle, "ask.js"))
-                    .initialization_script(INIT_SCRIPT);
+    Ok(())
+}
+
+/// Builds the window `label` with its own main, titlebar and ask views from the
+/// current config. `core` is the primary window, it restores the saved geometry
+/// and URL, keeps the reference pane and tracks state for the whole app. Others
+/// open centered on the start URL. Blocks on the event loop, so it must not be
+/// called from the main thread.
+pub fn create_main_window(handle: &AppHandle, label: &str) -> Result<Window, String> {
+    let conf = &AppConf::load(handle).map_err(|e| format!("Failed to load config: {}", e))?;
+    let is_core = label == "core";
+    let ask_mode_height = conf.ask_mode_height();
+    let custom_titlebar = conf.custom_titlebar;
+    let titlebar_height = conf.titlebar_mode_height();
+    let titlebar_position = conf.titlebar_position;
+    let split_ratio = split_ratio(label, conf);
+    let url = if is_core {
+        conf.initial_url()
+    } else {
+        conf.start_url()
+    };
+    let main_view = main_view(handle, label, conf, url);
+    // Without the custom titlebar the native, decorated one is used instead
+    let titlebar_view = custom_titlebar.then(|| titlebar_view(label, conf));
+    // Created on demand by `set_ask_mode` rather than kept around at zero height
+    let ask_view = conf.ask_mode.then(|| ask_view(label, conf));
+    let reference_view = split_ratio
+        .and(conf.reference_url())
+        .map(|url| reference_view(conf, url));
+    let opacity = conf.opacity;
+    let rounded_corners = conf.rounded_corners;
+    let vibrancy = conf.vibrancy.clone();
+    let background_color = conf.background_color(AppConf::get_theme(handle));
+    let (min_width, min_height) = conf.min_window_size();
+    let window_position = is_core
+        .then(|| restore_window_position(handle, conf))
+        .flatten();
+    let (window_width, window_height) = match (window_position, conf.window_x) {
+        // The saved position is off-screen, so the saved size is not trusted either
+        (None, Some(_)) if is_core => (WINDOW_WIDTH, WINDOW_HEIGHT),
+        _ => (conf.window_width, conf.window_height),
+    };
+
+    let mut core_window = WindowBuilder::new(handle, label).title(DEFAULT_WINDOW_TITLE);
 
-            let titlebar_view = WebviewBuilder::new(
-                "titlebar",
-                WebviewUrl::App("index.html".into()),
+    #[cfg(target_os = "macos")]
+    if custom_titlebar {
+        core_window = core_window
+            .title_bar_style(TitleBarStyle::Overlay)
+            .hidden_title(true);
+    }
+
+    core_window = core_window
+        .resizable(true)
+        .inner_size(window_width, window_height)
+        .min_inner_size(min_width, min_height)
+        .always_on_top(conf.stay_on_top)
+        .visible_on_all_workspaces(conf.visible_on_all_workspaces)
+        .theme(Some(AppConf::get_theme(handle)));
+
+    core_window = match window_position {
+        Some((x, y)) => core_window.position(x, y),
+        None => core_window.center(),
+    };
+
+    let win = core_window
+        .build()
+        .map_err(|e| format!("Failed to build window: {}", e))?;
+
+    let win_size = win
+        .inner_size()
+        .map_err(|e| format!("Failed to get window size: {}", e))?;
+    let scale_factor = win
+        .scale_factor()
+        .map_err(|e| format!("Failed to get scale factor: {}", e))?;
+    let layout = ViewLayout::new(
+        win_size,
+        scale_factor,
+        titlebar_height,
+        ask_mode_height,
+        titlebar_position,
+        split_ratio,
+        conf.ask_floats(),
+    );
+
+    #[cfg(target_os = "macos")]
+    let views = setup_macos_views(
+        &win,
+        &layout,
+        main_view,
+        titlebar_view,
+        ask_view,
+        reference_view,
+    );
+
+    #[cfg(not(target_os = "macos"))]
+    let views = setup_non_macos_views(
+        &win,
+        &layout,
+        titlebar_position,
+        main_view,
+        titlebar_view,
+        ask_view,
+        reference_view,
+    );
+
+    if let Err(e) = views {
+        error!(
+            "[core:window] Failed to attach all views, continuing with the rest: {}",
+            e
+        );
+    }
+
+    if let Err(e) = platform::set_background_color(&win, background_color) {
+        error!("[core:window] Failed to set background color: {}", e);
+    }
+
+    if let Some(material) = &vibrancy {
+        if let Err(e) = platform::set_vibrancy(&win, material) {
+            error!("[core:window] Failed to set vibrancy: {}", e);
+        }
+    }
+
+    if opacity < 1.0 {
+        if let Err(e) = platform::set_opacity(&win, opacity) {
+            error!("[core:window] Failed to set opacity: {}", e);
+        }
+    }
+
+    // macOS and Linux window managers round the corners on their own
+    if rounded_corners && cfg!(windows) {
+        if let Err(e) = platform::set_rounded_corners(&win) {
+            error!("[core:window] Failed to set rounded corners: {}", e);
+        }
+    }
+
+    let handle = handle.clone();
+    let window_clone = win.clone();
+    let geometry_generation = Arc::new(AtomicU64::new(0));
+    let focus_generation = Arc::new(AtomicU64::new(0));
+    let resize_throttle = Arc::new(Mutex::new(ResizeThrottle::default()));
+    let display = Mutex::new(cmd::display_info(&win).ok());
+    win.on_window_event(move |event| {
+        // Only the core window's geometry is restored on the next launch
+        if is_core && matches!(event, WindowEvent::Resized(_) | WindowEvent::Moved(_)) {
+            save_window_geometry(&handle, &geometry_generation);
+        }
+        if is_core
+            && matches!(
+                event,
+                WindowEvent::Moved(_) | WindowEvent::ScaleFactorChanged { .. }
             )
-            .auto_resize();
-
-            let ask_view =
-                WebviewBuilder::new("ask", WebviewUrl::App("index.html".into()))
-                    .auto_resize();
-
-            let win = window.lock().unwrap();
-            let scale_factor = win.scale_factor().unwrap();
-            let titlebar_height = (scale_factor * TITLEBAR_HEIGHT).round() as u32;
-            let ask_height = (scale_factor * ask_mode_height).round() as u32;
-
-            #[cfg(target_os = "macos")]
-            {
-                let main_area_height = win_size.height - titlebar_height;
-
-                win.add_child(
-                    titlebar_view,
-                    LogicalPosition::new(0, 0),
-                    PhysicalSize::new(win_size.width, titlebar_height),
-                )
-                .unwrap();
-                win.add_child(
-                    ask_view,
-                    LogicalPosition::new(
-                        0.0,
-                        (win_size.height as f64 / scale_factor) - ask_mode_height,
-                    ),
-                    PhysicalSize::new(win_size.width, ask_height),
-                )
-                .unwrap();
-                win.add_child(
-                    main_view,
-                    LogicalPosition::new(0.0, TITLEBAR_HEIGHT),
-                    PhysicalSize::new(win_size.width, main_area_height - ask_height),
-                )
-                .unwrap();
+        {
+            emit_display_changed(&handle, &window_clone, &display);
+        }
+
+        match event {
+            WindowEvent::Focused(true) => {
+                idle::reset(&handle);
+                if is_core {
+                    schedule_focus_composer(&handle, &focus_generation);
+                }
+            }
+            // A pending focus is dropped when the window is hidden again
+            WindowEvent::Focused(false) => {
+                focus_generation.fetch_add(1, Ordering::SeqCst);
+            }
+            WindowEvent::Resized(size) => {
+                schedule_layout(&handle, &window_clone, &resize_throttle, *size);
+            }
+            // The strips are sized in logical pixels, so they need a new
+            // layout on a monitor with a different scale
+            WindowEvent::ScaleFactorChanged { new_inner_size, .. } => {
+                schedule_layout(&handle, &window_clone, &resize_throttle, *new_inner_size);
+            }
+            // Other windows simply close along with their views
+            WindowEvent::CloseRequested { api, .. } if is_core => {
+                let conf = &AppConf::load(&handle).unwrap_or_else(|e| {
+                    error!("[core:window] Failed to load config, using defaults: {}", e);
+                    AppConf::new()
+                });
+                if conf.restore_last_url {
+                    save_last_url(&handle);
+                }
+                if conf.closes_to_tray() {
+                    api.prevent_close();
+                    if let Err(e) = window_clone.hide() {
+                        error!("[core:window] Failed to hide window: {}", e);
+                    }
+                }
             }
+            WindowEvent::ThemeChanged(_) => {
+                let conf = &AppConf::load(&handle).unwrap_or_else(|e| {
+                    error!("[core:window] Failed to load config, using defaults: {}", e);
+                    AppConf::new()
+                });
+                // The event reaches every view, once is enough
+                if is_core && conf.theme == "system" {
+                    if let Err(e) = cmd::emit_theme_changed(&handle) {
+                        error!("[core:window] Failed to emit theme change: {}", e);
+                    }
+                }
+                // Some platforms reset the native alpha when the appearance changes
+                if conf.opacity < 1.0 {
+                    if let Err(e) = platform::set_opacity(&window_clone, conf.opacity) {
+                        error!("[core:window] Failed to set opacity: {}", e);
+                    }
+                }
+            }
+            _ => (),
+        }
+    });
 
-            #[cfg(not(target_os = "macos"))]
-            {
-                win.add_child(
-                    ask_view,
-                    LogicalPosition::new(
-                        0.0,
-                        (win_size.height as f64 / scale_factor) - ask_mode_height,
-                    ),
-                    PhysicalSize::new(win_size.width, ask_height),
-                )
-                .unwrap();
-                win.add_child(
-                    titlebar_view,
+    Ok(win)
+}
+
+/// Label of the `view` in `window`, the core window's views keep their bare
+/// labels since most commands look them up by those.
+pub fn view_label(window: &str, view: &str) -> String {
+    if window == "core" {
+        view.to_string()
+    } else {
+        format!("{}-{}", window, view)
+    }
+}
+
+/// Whether the view labelled `label` loads the app's own frontend, the main and
+/// reference views show remote pages.
+pub fn is_local_view(label: &str) -> bool {
+    label == WINDOW_SETTINGS
+        || ["titlebar", "ask"]
+            .iter()
+            .any(|view| label == *view || label.ends_with(&format!("-{}", view)))
+}
+
+/// Only the core window has a reference pane, see `set_reference_url`.
+fn split_ratio(window: &str, conf: &AppConf) -> Option<f64> {
+    conf.split_mode_ratio().filter(|_| window == "core")
+}
+
+/// Reports a failure that leaves the app without a usable window in a native
+/// dialog, then exits once it is dismissed. Anything recoverable should log and
+/// carry on instead.
+fn fatal_error(app: &AppHandle, what: &str, e: impl std::fmt::Display) {
+    error!("[core:setup] {}: {}", what, e);
+    let handle = app.clone();
+    app.dialog()
+        .message(format!("{}.\n\n{}", what, e))
+        .title(DEFAULT_WINDOW_TITLE)
+        .kind(MessageDialogKind::Error)
+        .show(move |_| handle.exit(1));
+}
+
+/// Injects `REDUCE_MOTION_CSS`, behind a `prefers-reduced-motion` query for
+/// `auto` so the webview tracks the OS setting on its own.
+fn reduce_motion_script(mode: ReduceMotion) -> Option<String> {
+    let css = match mode {
+        ReduceMotion::Off => return None,
+        ReduceMotion::On => REDUCE_MOTION_CSS.to_string(),
+        ReduceMotion::Auto => format!(
+            "@media (prefers-reduced-motion: reduce) {{{}}}",
+            REDUCE_MOTION_CSS
+        ),
+    };
+    Some(format!(
+        r#"window.addEventListener('DOMContentLoaded', function() {{
+    const style = document.createElement('style');
+    style.textContent = {};
+    document.head.appendChild(style);
+}});"#,
+        serde_json::Value::from(css)
+    ))
+}
+
+/// App-wide events not tied to a single window's handler.
+pub fn handle_run_event(app: &AppHandle, event: RunEvent) {
+    if let RunEvent::Exit = event {
+        idle::cancel();
+    }
+    // Child webviews report drops themselves rather than through the window
+    if let RunEvent::WebviewEvent {
+        event: WebviewEvent::DragDrop(DragDropEvent::Drop { paths, .. }),
+        ..
+    } = event
+    {
+        upload::handle_drop(app, paths);
+    }
+}
+
+/// Emits `main-url-changed` for in-page route changes, full page loads are
+/// reported from `on_page_load`. The event names the main view it came from,
+/// its URL is read back from that webview rather than trusted from the page.
+fn watch_main_url(app: &AppHandle) {
+    let handle = app.clone();
+    app.listen_any("navigation:change", move |event| {
+        let label = serde_json::from_str::<serde_json::Value>(event.payload())
+            .ok()
+            .and_then(|payload| payload["view"].as_str().map(str::to_string))
+            .unwrap_or_else(|| "main".into());
+        let Some(main) = handle.get_webview(&label) else {
+            return;
+        };
+        if let Ok(url) = main.url() {
+            emit_main_url(&handle, main.window().label(), &url);
+        }
+    });
+}
+
+/// Tells the titlebar of `window` about a new URL of its main view.
+fn emit_main_url(app: &AppHandle, window: &str, url: &Url) {
+    navigation::visit(app, window, url);
+    let titlebar = view_label(window, "titlebar");
+    let payload = serde_json::json!({ "url": url });
+    if let Err(e) = app.emit_to(&titlebar, "main-url-changed", payload) {
+        error!("[view:main] Failed to emit main-url-changed: {}", e);
+    }
+}
+
+/// Shows the onboarding overlay once the titlebar is listening, then clears
+/// `first_run`. Without the custom titlebar it waits until one is turned on.
+pub fn watch_first_run(app: &AppHandle) {
+    let handle = app.clone();
+    app.once_any("titlebar:ready", move |_| {
+        let result = AppConf::load(&handle).and_then(|conf| {
+            // Already shown by an earlier listener, see `reset_onboarding`
+            if !conf.first_run {
+                return Ok(());
+            }
+            handle.emit_to("titlebar", "first-run", ())?;
+            conf.amend(serde_json::json!({ "first_run": false }))?
+                .save(&handle)
+        });
+        if let Err(e) = result {
+            error!("[view:titlebar] Failed to show onboarding: {}", e);
+        }
+    });
+}
+
+/// Persists the main webview's current URL so it can be reopened on startup.
+fn save_last_url(app: &AppHandle) {
+    let Some(main) = app.get_webview("main") else {
+        return;
+    };
+    let result = main.url().map_err(|e| e.into()).and_then(|url| {
+        AppConf::load(app)?
+            .amend(serde_json::json!({ "last_url": url }))?
+            .save(app)
+    });
+    if let Err(e) = result {
+        error!("[core:window] Failed to save last url: {}", e);
+    }
+}
+
+/// Emits `display-changed` once the window is on another monitor, or its
+/// monitor's scale or work area changed, since the last call.
+fn emit_display_changed(app: &AppHandle, win: &Window, last: &Mutex<Option<cmd::DisplayInfo>>) {
+    let info = match cmd::display_info(win) {
+        Ok(info) => info,
+        Err(e) => {
+            warn!("[core:display] {}", e);
+            return;
+        }
+    };
+    let mut last = lock(last);
+    if last.as_ref() != Some(&info) {
+        if let Err(e) = app.emit("display-changed", &info) {
+            error!("[core:display] Failed to emit display change: {}", e);
+        }
+        *last = Some(info);
+    }
+}
+
+/// Positions and sizes of the child webviews for a given window size.
+pub struct ViewLayout {
+    pub main: (LogicalPosition<f64>, PhysicalSize<u32>),
+    pub titlebar: (LogicalPosition<f64>, PhysicalSize<u32>),
+    pub ask: (LogicalPosition<f64>, PhysicalSize<u32>),
+    pub reference: (LogicalPosition<f64>, PhysicalSize<u32>),
+    /// The ask strip overlaps the bottom of the main row, so it must be added last.
+    #[cfg(not(target_os = "macos"))]
+    pub ask_floats: bool,
+}
+
+impl ViewLayout {
+    /// Computes the layout, `titlebar_height` and `ask_mode_height` are in logical pixels.
+    /// With a `split_ratio` the reference pane takes the rest of the main view's row,
+    /// the strips keep the full width. With `ask_floats` the main row reaches under
+    /// the ask strip.
+    pub fn new(
+        size: PhysicalSize<u32>,
+        scale_factor: f64,
+        titlebar_height: f64,
+        ask_mode_height: f64,
+        titlebar_position: TitlebarPosition,
+        split_ratio: Option<f64>,
+        ask_floats: bool,
+    ) -> Self {
+        let titlebar_px = (scale_factor * titlebar_height).round() as u32;
+        let ask_px = (scale_factor * ask_mode_height).round() as u32;
+        let logical_height = size.height as f64 / scale_factor;
+        let reserved_px = if ask_floats {
+            titlebar_px
+        } else {
+            titlebar_px.saturating_add(ask_px)
+        };
+        let main_height = size.height.saturating_sub(reserved_px);
+        let main_width = match split_ratio {
+            Some(ratio) => (size.width as f64 * ratio).round() as u32,
+            None => size.width,
+        };
+        // A window dragged smaller than the strips must not underflow or yield empty views
+        let main_size = view_size(main_width, main_height);
+        let reference_size = view_size(size.width.saturating_sub(main_width), main_height);
+        let reference_x = main_width as f64 / scale_factor;
+        let titlebar_size = view_size(size.width, titlebar_px);
+        let ask_size = view_size(size.width, ask_px);
+
+        let titlebar_position = if cfg!(target_os = "macos") {
+            TitlebarPosition::Top
+        } else {
+            titlebar_position
+        };
+
+        // The ask strip always stays right below the main view
+        match titlebar_position {
+            TitlebarPosition::Top => Self {
+                main: (LogicalPosition::new(0.0, titlebar_height), main_size),
+                reference: (
+                    LogicalPosition::new(reference_x, titlebar_height),
+                    reference_size,
+                ),
+                titlebar: (LogicalPosition::new(0.0, 0.0), titlebar_size),
+                ask: (
+                    LogicalPosition::new(0.0, (logical_height - ask_mode_height).max(0.0)),
+                    ask_size,
+                ),
+                #[cfg(not(target_os = "macos"))]
+                ask_floats,
+            },
+            TitlebarPosition::Bottom => Self {
+                main: (LogicalPosition::new(0.0, 0.0), main_size),
+                reference: (LogicalPosition::new(reference_x, 0.0), reference_size),
+                titlebar: (
+                    LogicalPosition::new(0.0, (logical_height - titlebar_height).max(0.0)),
+                    titlebar_size,
+                ),
+                ask: (
                     LogicalPosition::new(
                         0.0,
-                        (win_size.height as f64 / scale_factor) - ask_mode_height - TITLEBAR_HEIGHT,
+                        (logical_height - ask_mode_height - titlebar_height).max(0.0),
                     ),
-                    PhysicalSize::new(win_size.width, titlebar_height),
-                )
-                .unwrap();
-                win.add_child(
-                    main_view,
-                    LogicalPosition::new(0.0, 0.0),
-                    PhysicalSize::new(
-                        win_size.width,
-                        win_size.height - (ask_height + titlebar_height),
-                    ),
-                )
-                .unwrap();
-            }
+                    ask_size,
+                ),
+                #[cfg(not(target_os = "macos"))]
+                ask_floats,
+            },
+        }
+    }
+}
+
+/// A view size with both dimensions clamped to at least one pixel.
+fn view_size(width: u32, height: u32) -> PhysicalSize<u32> {
+    PhysicalSize::new(width.max(1), height.max(1))
+}
 
-            let window_clone = Arc::clone(&window);
-            let set_view_properties =
-                |view: &tauri::Webview, position: LogicalPosition<f64>, size: PhysicalSize<u32>| {
-                    if let Err(e) = view.set_position(position) {
-                        eprintln!("[view:position] Failed to set view position: {}", e);
+#[cfg(target_os = "macos")]
+fn setup_macos_views(
+    win: &Window,
+    layout: &ViewLayout,
+    main_view: WebviewBuilder<Wry>,
+    titlebar_view: Option<WebviewBuilder<Wry>>,
+    ask_view: Option<WebviewBuilder<Wry>>,
+    reference_view: Option<WebviewBuilder<Wry>>,
+) -> Result<(), tauri::Error> {
+    let mut views = Vec::new();
+    if let Some(titlebar_view) = titlebar_view {
+        views.push(("titlebar", titlebar_view, layout.titlebar));
+    }
+    if let Some(ask_view) = ask_view {
+        views.push(("ask", ask_view, layout.ask));
+    }
+    if let Some(reference_view) = reference_view {
+        views.push(("reference", reference_view, layout.reference));
+    }
+    views.push(("main", main_view, layout.main));
+    add_child_views(win, views)
+}
+
+/// Attaches the titlebar and ask strips in screen order, then the reference pane
+/// and the main view. A floating ask strip comes last, on top of the main view.
+#[cfg(not(target_os = "macos"))]
+fn setup_non_macos_views(
+    win: &Window,
+    layout: &ViewLayout,
+    titlebar_position: TitlebarPosition,
+    main_view: WebviewBuilder<Wry>,
+    titlebar_view: Option<WebviewBuilder<Wry>>,
+    ask_view: Option<WebviewBuilder<Wry>>,
+    reference_view: Option<WebviewBuilder<Wry>>,
+) -> Result<(), tauri::Error> {
+    let mut views = Vec::new();
+    let titlebar = titlebar_view.map(|view| ("titlebar", view, layout.titlebar));
+    let ask = ask_view.map(|view| ("ask", view, layout.ask));
+    let (ask, floating_ask) = if layout.ask_floats {
+        (None, ask)
+    } else {
+        (ask, None)
+    };
+    match titlebar_position {
+        TitlebarPosition::Top => views.extend(titlebar.into_iter().chain(ask)),
+        TitlebarPosition::Bottom => views.extend(ask.into_iter().chain(titlebar)),
+    }
+    views.extend(reference_view.map(|view| ("reference", view, layout.reference)));
+    views.push(("main", main_view, layout.main));
+    views.extend(floating_ask);
+    add_child_views(win, views)
+}
+
+/// Builder for the main ChatGPT view of `window`, shared by `create_main_window`
+/// and `switch_profile`. Loads and URL changes go to the titlebar of `window`.
+pub fn main_view(
+    handle: &AppHandle,
+    window: &str,
+    conf: &AppConf,
+    url: Url,
+) -> WebviewBuilder<Wry> {
+    let is_core = window == "core";
+    let label = view_label(window, "main");
+    let titlebar = view_label(window, "titlebar");
+    let window = window.to_string();
+    let mut main_view = with_layout(WebviewBuilder::new(label, WebviewUrl::External(url)), conf)
+        .on_download({
+            let app_handle = handle.clone();
+            let pending = PendingDownloads::default();
+            let titlebar = titlebar.clone();
+            move |_, event| handle_download_event(&app_handle, &titlebar, &pending, event)
+        })
+        .on_navigation({
+            let app_handle = handle.clone();
+            move |url| handle_navigation(&app_handle, url)
+        })
+        .on_page_load({
+            let app_handle = handle.clone();
+            move |view, payload| {
+                let url = payload.url();
+                let event = match payload.event() {
+                    PageLoadEvent::Started => {
+                        // Each host keeps its own zoom
+                        if let Ok(conf) = AppConf::load(&app_handle) {
+                            if let Err(e) = view.set_zoom(conf.zoom_for(url)) {
+                                error!("[view:main] Failed to set zoom: {}", e);
+                            }
+                        }
+                        if is_core {
+                            offline::check(&app_handle, url);
+                        }
+                        emit_main_url(&app_handle, &window, url);
+                        "main-loading"
                     }
-                    if let Err(e) = view.set_size(size) {
-                        eprintln!("[view:size] Failed to set view size: {}", e);
+                    PageLoadEvent::Finished => {
+                        let (collapsed, muted) = AppConf::load(&app_handle)
+                            .map(|conf| (conf.sidebar_collapsed, conf.muted))
+                            .unwrap_or_default();
+                        if collapsed {
+                            if let Err(e) = view.eval(COLLAPSE_SIDEBAR_SCRIPT) {
+                                error!("[view:main] Failed to collapse sidebar: {}", e);
+                            }
+                        }
+                        if muted {
+                            if let Err(e) = view.eval(&cmd::set_muted_script(true)) {
+                                error!("[view:main] Failed to mute: {}", e);
+                            }
+                        }
+                        if is_core {
+                            model::apply_default(&app_handle);
+                        }
+                        "main-loaded"
                     }
                 };
+                let payload = serde_json::json!({ "url": url });
+                if let Err(e) = app_handle.emit_to(&titlebar, event, payload) {
+                    error!("[view:main] Failed to emit {}: {}", event, e);
+                }
+            }
+        });
+    main_view = if is_safe_mode() {
+        main_view.initialization_script(INIT_SCRIPT)
+    } else {
+        with_scripts(handle, conf, is_core, main_view)
+    };
+    if let Some(proxy_url) = conf.proxy_url() {
+        main_view = main_view.proxy_url(proxy_url);
+    }
+    if !conf.user_agent.is_empty() {
+        main_view = main_view.user_agent(&conf.user_agent);
+    }
+    if conf.incognito {
+        return main_view.incognito(true);
+    }
+    match AppConf::get_profile_path(handle, &conf.profile) {
+        Ok(Some(data_dir)) => {
+            if cfg!(target_os = "macos") {
+                warn!("[view:main] WKWebView has no data directory, profiles share storage");
+            }
+            main_view = main_view.data_directory(data_dir);
+        }
+        Ok(None) => (),
+        Err(e) => error!(
+            "[view:main] Failed to resolve profile `{}`: {}",
+            conf.profile, e
+        ),
+    }
+    main_view
+}
 
-            win.on_window_event(move |event| {
-                let conf = &AppConf::load(&handle).unwrap();
-                let ask_mode_height = if conf.ask_mode { ASK_HEIGHT } else { 0.0 };
-                let ask_height = (scale_factor * ask_mode_height).round() as u32;
-
-                if let WindowEvent::Resized(size) = event {
-                    let win = window_clone.lock().unwrap();
-
-                    let main_view = win
-                        .get_webview("main")
-                        .expect("[view:main] Failed to get webview window");
-                    let titlebar_view = win
-                        .get_webview("titlebar")
-                        .expect("[view:titlebar] Failed to get webview window");
-                    let ask_view = win
-                        .get_webview("ask")
-                        .expect("[view:ask] Failed to get webview window");
-
-                    #[cfg(target_os = "macos")]
-                    {
-                        set_view_properties(
-                            &main_view,
-                            LogicalPosition::new(0.0, TITLEBAR_HEIGHT),
-                            PhysicalSize::new(
-                                size.width,
-                                size.height - (titlebar_height + ask_height),
-                            ),
-                        );
-                        set_view_properties(
-                            &titlebar_view,
-                            LogicalPosition::new(0.0, 0.0),
-                            PhysicalSize::new(size.width, titlebar_height),
-                        );
-                        set_view_properties(
-                            &ask_view,
-                            LogicalPosition::new(
-                                0.0,
-                                (size.height as f64 / scale_factor) - ask_mode_height,
-                            ),
-                            PhysicalSize::new(size.width, ask_height),
-                        );
-                    }
+/// Adds the main view's initialization scripts, everything but `INIT_SCRIPT` is
+/// left out in safe mode.
+fn with_scripts(
+    handle: &AppHandle,
+    conf: &AppConf,
+    is_core: bool,
+    mut main_view: WebviewBuilder<Wry>,
+) -> WebviewBuilder<Wry> {
+    // ask.js lives in the scripts folder too, alongside any user scripts
+    for script in AppConf::load_scripts_dir(handle, conf.script_preset.as_deref()) {
+        main_view = main_view.initialization_script(&script);
+    }
+    main_view = main_view
+        .initialization_script(INIT_SCRIPT)
+        .initialization_script(&auth_popup_script(conf));
+    main_view = main_view.initialization_script(&shortcuts::page_script(conf));
+    if conf.spellcheck {
+        main_view = main_view.initialization_script(SPELLCHECK_SCRIPT);
+    }
+    if conf.idle_reload_minutes > 0 {
+        main_view = main_view.initialization_script(IDLE_ACTIVITY_SCRIPT);
+    }
+    if conf.notify_on_complete && is_core {
+        main_view = main_view.initialization_script(RESPONSE_COMPLETE_SCRIPT);
+    }
+    if let Some(script) = reduce_motion_script(conf.reduce_motion) {
+        main_view = main_view.initialization_script(&script);
+    }
+    if let Some(style) = AppConf::load_style(handle, "custom.css") {
+        main_view = main_view.initialization_script(&style);
+    }
+    main_view
+}
 
-                    #[cfg(not(target_os = "macos"))]
-                    {
-                        set_view_properties(
-                            &main_view,
-                            LogicalPosition::new(0.0, 0.0),
-                            PhysicalSize::new(
-                                size.width,
-                                size.height - (ask_height + titlebar_height),
-                            ),
-                        );
-                        set_view_properties(
-                            &titlebar_view,
-                            LogicalPosition::new(
-                                0.0,
-                                (size.height as f64 / scale_factor) - TITLEBAR_HEIGHT,
-                            ),
-                            PhysicalSize::new(size.width, titlebar_height),
-                        );
-                        set_view_properties(
-                            &ask_view,
-                            LogicalPosition::new(
-                                0.0,
-                                (size.height as f64 / scale_factor)
-                                    - ask_mode_height
-                                    - TITLEBAR_HEIGHT,
-                            ),
-                            PhysicalSize::new(size.width, ask_height),
-                        );
-                    }
-                }
-            });
+/// Builder for the custom titlebar of `window`, shared by `create_main_window`
+/// and `toggle_titlebar`.
+pub fn titlebar_view(window: &str, conf: &AppConf) -> WebviewBuilder<Wry> {
+    with_layout(
+        WebviewBuilder::new(
+            view_label(window, "titlebar"),
+            WebviewUrl::App("index.html".into()),
+        ),
+        conf,
+    )
+}
+
+/// Builder for the ask strip of `window`, shared by `create_main_window` and
+/// `set_ask_mode`.
+pub fn ask_view(window: &str, conf: &AppConf) -> WebviewBuilder<Wry> {
+    let view = with_layout(
+        WebviewBuilder::new(
+            view_label(window, "ask"),
+            WebviewUrl::App("index.html".into()),
+        ),
+        conf,
+    );
+    if conf.transparent_ask {
+        transparent(view)
+    } else {
+        view
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+fn transparent(view: WebviewBuilder<Wry>) -> WebviewBuilder<Wry> {
+    view.transparent(true)
+}
+
+/// Transparent webviews need the `macos-private-api` feature, which the app
+/// doesn't enable, so the view stays opaque.
+#[cfg(target_os = "macos")]
+fn transparent(view: WebviewBuilder<Wry>) -> WebviewBuilder<Wry> {
+    warn!("[view:ask] Transparent webviews are unsupported on macOS, ignoring transparent_ask");
+    view
+}
+
+/// Builder for the core window's reference pane, shared by `create_main_window`
+/// and `set_reference_url`.
+pub fn reference_view(conf: &AppConf, url: Url) -> WebviewBuilder<Wry> {
+    with_layout(
+        WebviewBuilder::new("reference", WebviewUrl::External(url)),
+        conf,
+    )
+}
+
+/// Lets the webview follow window resizes on its own unless `manual_layout_only`
+/// leaves sizing to `update_view_positions` alone.
+fn with_layout(view: WebviewBuilder<Wry>, conf: &AppConf) -> WebviewBuilder<Wry> {
+    if conf.manual_layout_only {
+        view
+    } else {
+        view.auto_resize()
+    }
+}
+
+/// Attaches the views in order, a failing view doesn't prevent the remaining ones
+/// from being added. Returns the first error encountered.
+#[allow(clippy::type_complexity)]
+fn add_child_views(
+    win: &Window,
+    views: Vec<(
+        &str,
+        WebviewBuilder<Wry>,
+        (LogicalPosition<f64>, PhysicalSize<u32>),
+    )>,
+) -> Result<(), tauri::Error> {
+    let views = views
+        .into_iter()
+        .map(|(name, view, bounds)| (name, (view, bounds)))
+        .collect();
+    add_each(views, |name, (view, (position, size))| {
+        let view = win.add_child(view, position, size)?;
+        // Recovery recreates the core window's main view only
+        if name == "main" && win.label() == "core" {
+            crash::watch(win.app_handle(), &view);
+        }
+        Ok(())
+    })
+}
+
+/// Runs `add` for every view in order, logging and carrying on past failures.
+/// Returns the first error encountered.
+fn add_each<V, E: std::fmt::Display>(
+    views: Vec<(&str, V)>,
+    mut add: impl FnMut(&str, V) -> Result<(), E>,
+) -> Result<(), E> {
+    let mut result = Ok(());
+    for (name, view) in views {
+        if let Err(e) = add(name, view) {
+            error!("[view:{}] Failed to add webview: {}", name, e);
+            if result.is_ok() {
+                result = Err(e);
+            }
+        }
+    }
+    result
+}
+
+/// Re-applies the view layout of `win` alone, used on window resize and when ask
+/// mode is toggled. The scale factor is read fresh since the window may have
+/// moved to another monitor.
+pub fn update_view_positions(win: &Window, conf: &AppConf, size: PhysicalSize<u32>) {
+    let scale_factor = match win.scale_factor() {
+        Ok(scale_factor) => scale_factor,
+        Err(e) => {
+            error!("[core:window] Failed to get scale factor: {}", e);
+            return;
+        }
+    };
+    let layout = ViewLayout::new(
+        size,
+        scale_factor,
+        conf.titlebar_mode_height(),
+        conf.ask_mode_height(),
+        conf.titlebar_position,
+        split_ratio(win.label(), conf),
+        conf.ask_floats(),
+    );
+
+    // Views that failed to attach during setup, or are turned off, are skipped
+    for (label, (position, size)) in [
+        ("main", layout.main),
+        ("titlebar", layout.titlebar),
+        ("ask", layout.ask),
+        ("reference", layout.reference),
+    ] {
+        // Labels are unique app-wide, so look up this window's own views
+        if let Some(view) = win.get_webview(&view_label(win.label(), label)) {
+            set_view_properties(&view, position, size);
+        }
+    }
+}
+
+fn set_view_properties(view: &Webview, position: LogicalPosition<f64>, size: PhysicalSize<u32>) {
+    if let Err(e) = view.set_position(position) {
+        error!("[view:position] Failed to set view position: {}", e);
+    }
+    if let Err(e) = view.set_size(size) {
+        error!("[view:size] Failed to set view size: {}", e);
+    }
+}
+
+/// Locks the mutex, recovering the guard if a previous holder panicked.
+pub fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Returns the saved window position if it still lands on a connected monitor.
+fn restore_window_position(app: &AppHandle, conf: &AppConf) -> Option<(f64, f64)> {
+    let (x, y) = (conf.window_x?, conf.window_y?);
+    let monitors = app.available_monitors().ok()?;
+
+    let on_screen = monitors.iter().any(|monitor| {
+        let scale_factor = monitor.scale_factor();
+        let position = monitor.position().to_logical::<f64>(scale_factor);
+        let size = monitor.size().to_logical::<f64>(scale_factor);
+        x >= position.x
+            && x < position.x + size.width
+            && y >= position.y
+            && y < position.y + size.height
+    });
+
+    on_screen.then_some((x, y))
+}
+
+/// Coalesces resize events so the views are laid out at most once per
+/// `RESIZE_LAYOUT_INTERVAL`.
+struct ResizeThrottle {
+    last_applied: Instant,
+    /// Latest size not laid out yet, picked up by the scheduled pass.
+    pending: Option<PhysicalSize<u32>>,
+}
+
+impl Default for ResizeThrottle {
+    fn default() -> Self {
+        Self {
+            last_applied: Instant::now(),
+            pending: None,
+        }
+    }
+}
+
+/// Records `size` and schedules a layout pass unless one is already waiting. The
+/// pass uses the latest size, so the final size of a drag is always applied.
+fn schedule_layout(
+    app: &AppHandle,
+    window: &Window,
+    throttle: &Arc<Mutex<ResizeThrottle>>,
+    size: PhysicalSize<u32>,
+) {
+    let wait = {
+        let mut state = lock(throttle);
+        if state.pending.replace(size).is_some() {
+            return;
+        }
+        Duration::from_millis(RESIZE_LAYOUT_INTERVAL).saturating_sub(state.last_applied.elapsed())
+    };
+
+    let app = app.clone();
+    let window = window.clone();
+    let throttle = Arc::clone(throttle);
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(wait).await;
+        let pending = {
+            let mut state = lock(&throttle);
+            state.last_applied = Instant::now();
+            state.pending.take()
+        };
+        let Some(size) = pending else {
+            return;
+        };
+
+        match AppConf::load(&app) {
+            Ok(conf) => update_view_positions(&window, &conf, size),
+            Err(e) => error!("[core:window] Failed to load config for layout: {}", e),
         }
     });
+}
 
-    Ok(())
+/// Runs `focus_composer` with `focus_composer_on_show` once the core window has
+/// kept the focus for `FOCUS_COMPOSER_DELAY`.
+fn schedule_focus_composer(app: &AppHandle, generation: &Arc<AtomicU64>) {
+    let current = generation.fetch_add(1, Ordering::SeqCst) + 1;
+    let generation = Arc::clone(generation);
+    let app = app.clone();
+
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(FOCUS_COMPOSER_DELAY)).await;
+        if generation.load(Ordering::SeqCst) != current {
+            return;
+        }
+        let Ok(conf) = AppConf::load(&app) else {
+            return;
+        };
+        if !conf.focus_composer_on_show || app.get_webview("ask").is_some() {
+            return;
+        }
+        if let Err(e) = cmd::focus_composer(app) {
+            error!("[core:window] {}", e);
+        }
+    });
+}
+
+/// Persists the window size and position once resize/move events have settled.
+fn save_window_geometry(app: &AppHandle, generation: &Arc<AtomicU64>) {
+    let current = generation.fetch_add(1, Ordering::SeqCst) + 1;
+    let generation = Arc::clone(generation);
+    let app = app.clone();
+
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(GEOMETRY_SAVE_DELAY)).await;
+        if generation.load(Ordering::SeqCst) != current {
+            return;
+        }
+
+        let Some(win) = app.get_window("core") else {
+            return;
+        };
+        // Minimized/maximized geometry is not what we want to restore on next launch
+        if win.is_minimized().unwrap_or(false) || win.is_maximized().unwrap_or(false) {
+            return;
+        }
+
+        let (Ok(scale_factor), Ok(size), Ok(position)) =
+            (win.scale_factor(), win.inner_size(), win.outer_position())
+        else {
+            return;
+        };
+        let size = size.to_logical::<f64>(scale_factor);
+        let position = position.to_logical::<f64>(scale_factor);
+
+        let result = AppConf::load(&app)
+            .and_then(|conf| {
+                Ok(conf.amend(serde_json::json!({
+                    "window_width": size.width,
+                    "window_height": size.height,
+                    "window_x": position.x,
+                    "window_y": position.y,
+                }))?)
+            })
+            .and_then(|conf| conf.save(&app));
+
+        if let Err(e) = result {
+            error!("[window:geometry] Failed to save window geometry: {}", e);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn is_local_view_accepts_only_the_app_frontend() {
+        for label in [
+            "titlebar",
+            "ask",
+            "settings",
+            "window-2-titlebar",
+            "window-2-ask",
+        ] {
+            assert!(is_local_view(label), "{}", label);
+        }
+        for label in ["main", "reference", "window-2-main", "asking", "window-2"] {
+            assert!(!is_local_view(label), "{}", label);
+        }
+    }
+
+    #[test]
+    fn add_each_carries_on_after_a_failing_view() {
+        let views = vec![("titlebar", 1), ("ask", 2), ("main", 3)];
+        let mut added = Vec::new();
+        let result = add_each(views, |name, view| {
+            if name == "ask" {
+                return Err(format!("no {}", name));
+            }
+            added.push(view);
+            Ok(())
+        });
+
+        assert_eq!(result, Err("no ask".to_string()));
+        assert_eq!(added, vec![1, 3]);
+    }
+
+    #[test]
+    fn add_each_returns_the_first_error() {
+        let views = vec![("titlebar", ()), ("ask", ()), ("main", ())];
+        let result = add_each(views, |name, _| Err(name.to_string()));
+
+        assert_eq!(result, Err("titlebar".to_string()));
+    }
+
+    fn layout(size: PhysicalSize<u32>, scale_factor: f64) -> ViewLayout {
+        ViewLayout::new(
+            size,
+            scale_factor,
+            28.0,
+            80.0,
+            TitlebarPosition::Top,
+            None,
+            false,
+        )
+    }
+
+    #[test]
+    fn view_layout_keeps_views_non_empty_in_a_tiny_window() {
+        for size in [PhysicalSize::new(0, 0), PhysicalSize::new(1, 1)] {
+            let layout = layout(size, 1.0);
+            for (position, size) in [layout.main, layout.titlebar, layout.ask, layout.reference] {
+                assert!(size.width >= 1 && size.height >= 1);
+                assert!(position.y >= 0.0);
+            }
+            assert_eq!(layout.ask.0.y, 0.0);
+        }
+    }
+
+    #[test]
+    fn view_layout_splits_a_tiny_window() {
+        let layout = ViewLayout::new(
+            PhysicalSize::new(1, 10),
+            1.0,
+            28.0,
+            80.0,
+            TitlebarPosition::Bottom,
+            Some(0.5),
+            false,
+        );
+
+        assert_eq!(layout.main.1, PhysicalSize::new(1, 1));
+        assert_eq!(layout.reference.1, PhysicalSize::new(1, 1));
+        assert_eq!(layout.titlebar.0.y, 0.0);
+    }
+
+    #[test]
+    fn view_layout_follows_a_scale_factor_change() {
+        let hidpi = layout(PhysicalSize::new(1600, 1200), 2.0);
+        let lodpi = layout(PhysicalSize::new(800, 600), 1.0);
+
+        assert_eq!(hidpi.titlebar.1, PhysicalSize::new(1600, 56));
+        assert_eq!(lodpi.titlebar.1, PhysicalSize::new(800, 28));
+        for (hi, lo) in [
+            (hidpi.main, lodpi.main),
+            (hidpi.titlebar, lodpi.titlebar),
+            (hidpi.ask, lodpi.ask),
+        ] {
+            assert_eq!(hi.0, lo.0);
+            assert_eq!(hi.1.width, lo.1.width * 2);
+            assert_eq!(hi.1.height, lo.1.height * 2);
+        }
+        assert_eq!(lodpi.ask.0.y, 600.0 - 80.0);
+    }
 }
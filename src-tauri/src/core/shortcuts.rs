@@ -0,0 +1,294 @@
+//! Keyboard shortcuts by action, read from `AppConf.shortcuts` over
+//! `DEFAULT_SHORTCUTS`. `toggle_window` is a global shortcut, `stop_generating`
+//! is bound inside the main view and everything else is a menu accelerator.
+
+use log::{error, warn};
+use std::collections::HashMap;
+use tauri::{command, AppHandle, Manager, Webview};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutState};
+
+use crate::core::{
+    cmd,
+    conf::AppConf,
+    constant::DEFAULT_SHORTCUTS,
+    menu::{self, FIXED_ACCELERATORS},
+};
+
+static GLOBAL_ACTION: &str = "toggle_window";
+static PAGE_ACTION: &str = "stop_generating";
+
+/// Registers the global shortcut and logs bindings that don't parse or clash.
+pub fn init(app: &AppHandle, conf: &AppConf) {
+    for (action, accelerator) in bindings(conf) {
+        if let Err(e) = validate(&accelerator) {
+            error!("[core:shortcut] Invalid shortcut for {}: {}", action, e);
+        }
+    }
+    for conflict in conflicts(conf) {
+        warn!("[core:shortcut] {}", conflict);
+    }
+    register_toggle_window(app, conf.shortcut(GLOBAL_ACTION));
+}
+
+/// The accelerator `action` is bound to, `None` when unbound. A binding that
+/// doesn't parse falls back to the default, it would fail the whole menu.
+pub fn accelerator(conf: &AppConf, action: &str) -> Option<String> {
+    let accelerator = conf.shortcut(action);
+    if accelerator.is_empty() {
+        return None;
+    }
+    if let Err(e) = validate(accelerator) {
+        let default = AppConf::new().shortcut(action).to_string();
+        warn!(
+            "[menu:accelerator] Invalid {} `{}`, using `{}`: {}",
+            action, accelerator, default, e
+        );
+        return Some(default).filter(|default| !default.is_empty());
+    }
+    Some(accelerator.to_string())
+}
+
+/// Binds `stop_generating` inside the main view, so it only applies while the
+/// view has focus and keys the page handles itself are left alone.
+pub fn page_script(conf: &AppConf) -> String {
+    format!(
+        "window.ChatAsk?.bindShortcut?.({}, {}, () => window.ChatAsk.stop());",
+        serde_json::Value::from(PAGE_ACTION),
+        serde_json::Value::from(conf.shortcut(PAGE_ACTION))
+    )
+}
+
+/// Every known action with its current accelerator.
+fn bindings(conf: &AppConf) -> Vec<(String, String)> {
+    DEFAULT_SHORTCUTS
+        .iter()
+        .map(|(action, _)| (action.to_string(), conf.shortcut(action).to_string()))
+        .collect()
+}
+
+/// An empty accelerator is valid and means unbound.
+fn validate(accelerator: &str) -> Result<(), String> {
+    if accelerator.is_empty() {
+        return Ok(());
+    }
+    accelerator
+        .parse::<Shortcut>()
+        .map(|_| ())
+        .map_err(|e| format!("`{}`: {}", accelerator, e))
+}
+
+/// Pairs of actions bound to the same keys, compared after parsing so
+/// `CmdOrCtrl+L` and `Ctrl+L` clash on Linux and Windows. The fixed menu
+/// accelerators count as taken.
+fn conflicts(conf: &AppConf) -> Vec<String> {
+    let mut seen: HashMap<Shortcut, String> = FIXED_ACCELERATORS
+        .iter()
+        .filter_map(|(item, accelerator)| {
+            let shortcut = accelerator.parse::<Shortcut>().ok()?;
+            Some((shortcut, format!("the {} menu item", item)))
+        })
+        .collect();
+    let mut conflicts = Vec::new();
+    for (action, accelerator) in bindings(conf) {
+        let Ok(shortcut) = accelerator.parse::<Shortcut>() else {
+            continue;
+        };
+        match seen.get(&shortcut) {
+            Some(other) => conflicts.push(format!(
+                "`{}` is bound to both {} and {}",
+                accelerator, other, action
+            )),
+            None => {
+                seen.insert(shortcut, action);
+            }
+        }
+    }
+    conflicts
+}
+
+/// Rebinds `action` and persists it, an empty `accelerator` unbinds it. Keys
+/// already taken by another action are refused.
+#[command]
+pub fn set_shortcut(
+    app: AppHandle,
+    webview: Webview,
+    action: String,
+    accelerator: String,
+) -> Result<(), String> {
+    cmd::require_local_view(&webview, "set_shortcut")?;
+    if !DEFAULT_SHORTCUTS.iter().any(|(name, _)| *name == action) {
+        return Err(format!("[cmd:set_shortcut] Unknown action `{}`", action));
+    }
+    validate(&accelerator).map_err(|e| format!("[cmd:set_shortcut] Invalid shortcut {}", e))?;
+
+    let conf = AppConf::load(&app)
+        .map_err(|e| format!("[cmd:set_shortcut] Failed to load config: {}", e))?;
+    let previous = conf.shortcut(GLOBAL_ACTION).to_string();
+    let mut shortcuts = conf.shortcuts.clone();
+    shortcuts.insert(action, accelerator);
+    let conf = conf
+        .amend(serde_json::json!({ "shortcuts": shortcuts }))
+        .map_err(|e| format!("[cmd:set_shortcut] {}", e))?;
+    if let Some(conflict) = conflicts(&conf).into_iter().next() {
+        return Err(format!("[cmd:set_shortcut] {}", conflict));
+    }
+    conf.save(&app)
+        .map_err(|e| format!("[cmd:set_shortcut] Failed to save config: {}", e))?;
+    apply(&app, &previous, &conf).map_err(|e| format!("[cmd:set_shortcut] {}", e))
+}
+
+/// Drops every rebinding, going back to `DEFAULT_SHORTCUTS`.
+#[command]
+pub fn reset_shortcuts(app: AppHandle, webview: Webview) -> Result<(), String> {
+    cmd::require_local_view(&webview, "reset_shortcuts")?;
+    let conf = AppConf::load(&app)
+        .map_err(|e| format!("[cmd:reset_shortcuts] Failed to load config: {}", e))?;
+    let previous = conf.shortcut(GLOBAL_ACTION).to_string();
+    let conf = conf
+        .amend(serde_json::json!({ "shortcuts": {} }))
+        .map_err(|e| format!("[cmd:reset_shortcuts] {}", e))?;
+    conf.save(&app)
+        .map_err(|e| format!("[cmd:reset_shortcuts] Failed to save config: {}", e))?;
+    apply(&app, &previous, &conf).map_err(|e| format!("[cmd:reset_shortcuts] {}", e))
+}
+
+/// Every action with the accelerator it is bound to, empty when unbound.
+#[command]
+pub fn get_shortcuts(app: AppHandle) -> Result<HashMap<String, String>, String> {
+    let conf = AppConf::load(&app).map_err(|e| format!("[cmd:get_shortcuts] {}", e))?;
+    Ok(bindings(&conf).into_iter().collect())
+}
+
+/// Moves the global shortcut from `previous`, rebuilds the menu for its
+/// accelerators and rebinds the main view's shortcut, so changes apply without
+/// a restart.
+fn apply(app: &AppHandle, previous: &str, conf: &AppConf) -> Result<(), String> {
+    let (old, new) = (previous, conf.shortcut(GLOBAL_ACTION));
+    if old != new {
+        if let Ok(shortcut) = old.parse::<Shortcut>() {
+            if let Err(e) = app.global_shortcut().unregister(shortcut) {
+                warn!("[core:shortcut] Failed to unregister `{}`: {}", old, e);
+            }
+        }
+        register_toggle_window(app, new);
+    }
+
+    menu::init(app)
+        .and_then(|menu| app.set_menu(menu))
+        .map_err(|e| format!("Failed to rebuild the menu: {}", e))?;
+
+    if let Some(main) = app.get_webview("main") {
+        main.eval(&page_script(conf))
+            .map_err(|e| format!("Failed to rebind {}: {}", PAGE_ACTION, e))?;
+    }
+    Ok(())
+}
+
+/// Registers the global shortcut that shows and focuses the `core` window,
+/// or hides it when it already has focus. Invalid accelerators are logged.
+fn register_toggle_window(app: &AppHandle, accelerator: &str) {
+    if accelerator.is_empty() {
+        return;
+    }
+    let shortcut = match accelerator.parse::<Shortcut>() {
+        Ok(shortcut) => shortcut,
+        Err(e) => {
+            error!(
+                "[core:shortcut] Invalid {} `{}`: {}",
+                GLOBAL_ACTION, accelerator, e
+            );
+            return;
+        }
+    };
+
+    let result = app
+        .global_shortcut()
+        .on_shortcut(shortcut, |app, _, event| {
+            if event.state() != ShortcutState::Pressed {
+                return;
+            }
+            let Some(win) = app.get_window("core") else {
+                return;
+            };
+
+            let result = if win.is_visible().unwrap_or(false) && win.is_focused().unwrap_or(false) {
+                win.hide()
+            } else {
+                win.show().and_then(|_| win.set_focus())
+            };
+            if let Err(e) = result {
+                error!("[core:shortcut] Failed to toggle window: {}", e);
+            }
+        });
+
+    if let Err(e) = result {
+        error!(
+            "[core:shortcut] Failed to register `{}`: {}",
+            accelerator, e
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn conf(action: &str, accelerator: &str) -> AppConf {
+        AppConf {
+            shortcuts: HashMap::from([(action.to_string(), accelerator.to_string())]),
+            ..AppConf::new()
+        }
+    }
+
+    #[test]
+    fn accelerator_uses_the_binding() {
+        assert_eq!(
+            accelerator(&AppConf::new(), "focus_composer").as_deref(),
+            Some("CmdOrCtrl+L")
+        );
+        assert_eq!(
+            accelerator(&conf("focus_composer", "Alt+Shift+F"), "focus_composer").as_deref(),
+            Some("Alt+Shift+F")
+        );
+    }
+
+    #[test]
+    fn accelerator_is_none_when_unbound() {
+        assert_eq!(
+            accelerator(&conf("focus_composer", ""), "focus_composer"),
+            None
+        );
+        assert_eq!(accelerator(&AppConf::new(), "no_such_action"), None);
+    }
+
+    #[test]
+    fn accelerator_falls_back_to_the_default_when_invalid() {
+        assert_eq!(
+            accelerator(&conf("focus_composer", "Ctrl+Nope"), "focus_composer").as_deref(),
+            Some("CmdOrCtrl+L")
+        );
+        // Without a default the invalid binding is dropped
+        assert_eq!(
+            accelerator(&conf("no_such_action", "+++"), "no_such_action"),
+            None
+        );
+    }
+
+    #[test]
+    fn conflicts_compare_parsed_shortcuts() {
+        let conf = conf("copy_response", "CmdOrCtrl+L");
+
+        assert_eq!(conflicts(&conf).len(), 1);
+        assert!(conflicts(&AppConf::new()).is_empty());
+    }
+
+    #[test]
+    fn conflicts_include_the_fixed_menu_accelerators() {
+        let conflicts = conflicts(&conf("copy_response", "CmdOrCtrl+R"));
+
+        assert_eq!(
+            conflicts,
+            vec!["`CmdOrCtrl+R` is bound to both the view_reload menu item and copy_response"]
+        );
+    }
+}
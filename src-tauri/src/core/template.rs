@@ -10,11 +10,14 @@ use std::{
 };
 
 pub static SCRIPT_ASK: &[u8] = include_bytes!("../../scripts/ask.js");
+// Has no @version, so it is only written when missing and user edits are kept
+pub static STYLE_CUSTOM: &[u8] = include_bytes!("../../scripts/custom.css");
 
 /// Struct representing the template with the script data.
 #[derive(Debug)]
 pub struct Template {
     pub ask: Vec<u8>,
+    pub custom_css: Vec<u8>,
 }
 
 impl Template {
@@ -23,10 +26,13 @@ impl Template {
         let template_dir = template_dir.as_ref();
         let mut template = Template::default();
 
-        let files = vec![(template_dir.join("ask.js"), &mut template.ask)];
+        let files = vec![
+            (template_dir.join("ask.js"), &mut template.ask),
+            (template_dir.join("custom.css"), &mut template.custom_css),
+        ];
 
-        for (filename, _) in files {
-            match update_or_create_file(&filename, SCRIPT_ASK) {
+        for (filename, data) in files {
+            match update_or_create_file(&filename, data) {
                 Ok(updated) => {
                     if updated {
                         info!("Script updated or created: {}", filename.display());
@@ -48,6 +54,7 @@ impl Default for Template {
     fn default() -> Template {
         Template {
             ask: Vec::from(SCRIPT_ASK),
+            custom_css: Vec::from(STYLE_CUSTOM),
         }
     }
 }
@@ -127,8 +134,12 @@ fn update_or_create_file<P: AsRef<Path>>(filename: P, new_data: &[u8]) -> Result
             let new_info = read_version_info(new_data)?;
             let current_info = read_version_info(&current_data);
 
+            // `read_version_info` yields an empty version when there is no @version
             match (
-                new_info.get("version").and_then(|v| v.as_str()),
+                new_info
+                    .get("version")
+                    .and_then(|v| v.as_str())
+                    .filter(|v| !v.is_empty()),
                 current_info,
             ) {
                 (Some(new_version), Ok(current_info)) => {
@@ -165,3 +176,24 @@ fn update_or_create_file<P: AsRef<Path>>(filename: P, new_data: &[u8]) -> Result
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn new_keeps_an_edited_custom_css() {
+        let dir = std::env::temp_dir().join(format!("chatgpt-template-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("custom.css"), "body { color: red; }").unwrap();
+
+        Template::new(&dir);
+        let custom_css = fs::read_to_string(dir.join("custom.css"));
+        let ask = fs::read(dir.join("ask.js"));
+        let _ = fs::remove_dir_all(&dir);
+
+        assert_eq!(custom_css.unwrap(), "body { color: red; }");
+        assert_eq!(ask.unwrap(), SCRIPT_ASK);
+    }
+}
@@ -0,0 +1,81 @@
+use log::error;
+use tauri::{
+    menu::{Menu, MenuItem},
+    tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent},
+    AppHandle, Manager,
+};
+
+use crate::core::cmd;
+
+/// Builds the tray icon, a left-click shows the `core` window. Only the menu
+/// hides it, a double-click sends two clicks and would hide it right again.
+///
+/// "Quit" goes through `cmd::quit`, so it isn't intercepted by `close_to_tray`
+/// but does honor `confirm_quit`.
+pub fn init(app: &AppHandle) -> tauri::Result<()> {
+    let toggle = MenuItem::with_id(app, "tray_toggle", "Show/Hide Window", true, None::<&str>)?;
+    let quit = MenuItem::with_id(app, "tray_quit", "Quit", true, None::<&str>)?;
+    let menu = Menu::with_items(app, &[&toggle, &quit])?;
+
+    let mut tray = TrayIconBuilder::with_id("tray")
+        .tooltip("ChatGPT")
+        .menu(&menu)
+        .menu_on_left_click(false)
+        .on_menu_event(|app, event| match event.id().as_ref() {
+            "tray_toggle" => toggle_window(app),
+            "tray_quit" => {
+                if let Err(e) = cmd::quit(app.clone()) {
+                    error!("[tray:quit] {}", e);
+                }
+            }
+            _ => (),
+        })
+        .on_tray_icon_event(|tray, event| {
+            if let TrayIconEvent::Click {
+                button: MouseButton::Left,
+                button_state: MouseButtonState::Up,
+                ..
+            } = event
+            {
+                show_window(tray.app_handle());
+            }
+        });
+
+    if let Some(icon) = app.default_window_icon() {
+        tray = tray.icon(icon.clone());
+    }
+
+    tray.build(app)?;
+    Ok(())
+}
+
+/// Hides the `core` window if it is visible and not minimized, otherwise
+/// restores and focuses it.
+pub fn toggle_window(app: &AppHandle) {
+    let Some(win) = app.get_window("core") else {
+        return;
+    };
+
+    if win.is_visible().unwrap_or(false) && !win.is_minimized().unwrap_or(false) {
+        if let Err(e) = win.hide() {
+            error!("[tray:toggle] Failed to hide window: {}", e);
+        }
+    } else {
+        show_window(app);
+    }
+}
+
+/// Shows, unminimizes and focuses the `core` window.
+pub fn show_window(app: &AppHandle) {
+    let Some(win) = app.get_window("core") else {
+        return;
+    };
+
+    let result = win
+        .show()
+        .and_then(|_| win.unminimize())
+        .and_then(|_| win.set_focus());
+    if let Err(e) = result {
+        error!("[tray:show] Failed to show window: {}", e);
+    }
+}
@@ -0,0 +1,196 @@
+//! Updates from the release feed of the configured `update_channel`, through
+//! `tauri-plugin-updater`. The plugin is behind the `updater` cargo feature and
+//! needs `plugins.updater.pubkey` in tauri.conf.json, without either the commands
+//! report `NO_UPDATER` and the titlebar hides its update UI.
+
+use serde::Serialize;
+use tauri::{command, AppHandle};
+
+#[cfg(feature = "updater")]
+use {
+    crate::core::{
+        conf::{AppConf, UpdateChannel},
+        constant::{UPDATE_ENDPOINT_BETA, UPDATE_ENDPOINT_STABLE},
+        setup::lock,
+    },
+    log::{error, info, warn},
+    std::sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+    tauri::{Emitter, Url},
+    tauri_plugin_updater::{Update, UpdaterExt},
+};
+
+/// Matched by the titlebar, keep in sync.
+static NO_UPDATER: &str = "This build has no updater";
+
+/// Payload of `update-available` and `update-ready`.
+#[derive(Serialize, Clone)]
+pub struct UpdateInfo {
+    pub version: String,
+    pub notes: Option<String>,
+}
+
+/// The update found by the last check, taken by `install_update`.
+#[cfg(feature = "updater")]
+static PENDING_UPDATE: Mutex<Option<Update>> = Mutex::new(None);
+
+/// Set once an update is installed, so a stray `confirm_restart_response`
+/// can't restart the app.
+#[cfg(feature = "updater")]
+static RESTART_PENDING: AtomicBool = AtomicBool::new(false);
+
+/// Set once the plugin is registered with a pubkey to verify updates against.
+#[cfg(feature = "updater")]
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Registers the plugin and, with `auto_update`, checks for an update in the
+/// background. Without a pubkey every install would fail its signature check,
+/// so the updater stays off.
+#[cfg(feature = "updater")]
+pub fn init(app: &AppHandle, auto_update: bool) {
+    let has_pubkey = app
+        .config()
+        .plugins
+        .0
+        .get("updater")
+        .and_then(|updater| updater.get("pubkey"))
+        .and_then(|pubkey| pubkey.as_str())
+        .is_some_and(|pubkey| !pubkey.trim().is_empty());
+    if !has_pubkey {
+        warn!("[core:updater] No `plugins.updater.pubkey` in tauri.conf.json, updates are off");
+        return;
+    }
+    if let Err(e) = app.plugin(tauri_plugin_updater::Builder::new().build()) {
+        error!("[core:updater] Failed to register the updater: {}", e);
+        return;
+    }
+    ENABLED.store(true, Ordering::SeqCst);
+    if auto_update {
+        let app = app.clone();
+        tauri::async_runtime::spawn(async move {
+            if let Err(e) = check(&app).await {
+                error!("[core:updater] {}", e);
+            }
+        });
+    }
+}
+
+#[cfg(not(feature = "updater"))]
+pub fn init(_app: &AppHandle, auto_update: bool) {
+    if auto_update {
+        log::info!("[core:updater] {}, auto_update is ignored", NO_UPDATER);
+    }
+}
+
+#[cfg(feature = "updater")]
+fn endpoint(channel: UpdateChannel) -> &'static str {
+    match channel {
+        UpdateChannel::Stable => UPDATE_ENDPOINT_STABLE,
+        UpdateChannel::Beta => UPDATE_ENDPOINT_BETA,
+    }
+}
+
+/// Returns the newer version on the channel's feed, if any, and emits
+/// `update-available` for it.
+#[cfg(feature = "updater")]
+async fn check(app: &AppHandle) -> Result<Option<UpdateInfo>, String> {
+    if !ENABLED.load(Ordering::SeqCst) {
+        return Err(NO_UPDATER.into());
+    }
+    let conf = AppConf::load(app).map_err(|e| format!("Failed to load config: {}", e))?;
+    let endpoint = Url::parse(endpoint(conf.update_channel)).map_err(|e| e.to_string())?;
+    let update = app
+        .updater_builder()
+        .endpoints(vec![endpoint])
+        .build()
+        .map_err(|e| e.to_string())?
+        .check()
+        .await
+        .map_err(|e| format!("Failed to check for updates: {}", e))?;
+
+    let Some(update) = update else {
+        *lock(&PENDING_UPDATE) = None;
+        return Ok(None);
+    };
+    let info = UpdateInfo {
+        version: update.version.clone(),
+        notes: update.body.clone(),
+    };
+    info!("[core:updater] Version {} is available", info.version);
+    *lock(&PENDING_UPDATE) = Some(update);
+    app.emit("update-available", &info)
+        .map_err(|e| e.to_string())?;
+    Ok(Some(info))
+}
+
+#[command]
+pub async fn check_for_updates(app: AppHandle) -> Result<Option<UpdateInfo>, String> {
+    #[cfg(feature = "updater")]
+    return check(&app)
+        .await
+        .map_err(|e| format!("[cmd:check_for_updates] {}", e));
+
+    #[cfg(not(feature = "updater"))]
+    {
+        let _ = app;
+        Err(format!("[cmd:check_for_updates] {}", NO_UPDATER))
+    }
+}
+
+/// Downloads and installs the update found by `check_for_updates`, reporting
+/// `update-downloading` progress. Then `update-ready` asks the titlebar to
+/// confirm the restart, answered with `confirm_restart_response`. The Windows
+/// installer quits the app by itself instead.
+#[command]
+pub async fn install_update(app: AppHandle) -> Result<(), String> {
+    #[cfg(feature = "updater")]
+    {
+        if !ENABLED.load(Ordering::SeqCst) {
+            return Err(format!("[cmd:install_update] {}", NO_UPDATER));
+        }
+        let update = lock(&PENDING_UPDATE)
+            .take()
+            .ok_or("[cmd:install_update] No update available, check for updates first")?;
+        let mut received = 0;
+        update
+            .download_and_install(
+                |chunk, total| {
+                    received += chunk as u64;
+                    let progress = serde_json::json!({ "received": received, "total": total });
+                    if let Err(e) = app.emit("update-downloading", progress) {
+                        error!("[cmd:install_update] Failed to emit progress: {}", e);
+                    }
+                },
+                || (),
+            )
+            .await
+            .map_err(|e| format!("[cmd:install_update] Failed to install update: {}", e))?;
+
+        RESTART_PENDING.store(true, Ordering::SeqCst);
+        let info = UpdateInfo {
+            version: update.version.clone(),
+            notes: update.body.clone(),
+        };
+        app.emit_to("titlebar", "update-ready", info)
+            .map_err(|e| format!("[cmd:install_update] {}", e))
+    }
+
+    #[cfg(not(feature = "updater"))]
+    {
+        let _ = app;
+        Err(format!("[cmd:install_update] {}", NO_UPDATER))
+    }
+}
+
+#[command]
+pub fn confirm_restart_response(app: AppHandle, confirmed: bool) {
+    #[cfg(feature = "updater")]
+    if RESTART_PENDING.swap(false, Ordering::SeqCst) && confirmed {
+        app.restart();
+    }
+
+    #[cfg(not(feature = "updater"))]
+    let _ = (app, confirmed);
+}
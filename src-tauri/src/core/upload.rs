@@ -0,0 +1,143 @@
+use base64::{engine::general_purpose::STANDARD, Engine};
+use log::error;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::core::constant::{UPLOAD_BLOCKED_EXTENSIONS, UPLOAD_MAX_FILES, UPLOAD_MAX_SIZE};
+
+/// Attaches files dropped on any of the window's views to the ChatGPT composer.
+/// Files ChatGPT would refuse anyway, and those past `UPLOAD_MAX_SIZE` in total,
+/// are skipped with a `toast`, as is the whole drop when the page has no file input.
+pub fn handle_drop(app: &AppHandle, paths: Vec<PathBuf>) {
+    let app = app.clone();
+    // Reading large files must not stall the event loop
+    tauri::async_runtime::spawn_blocking(move || {
+        let mut rejected = Vec::new();
+        let mut files = Vec::new();
+        let mut budget = UPLOAD_MAX_SIZE;
+        for path in paths {
+            if files.len() == UPLOAD_MAX_FILES {
+                rejected.push(format!("{} (too many files)", file_name(&path)));
+                continue;
+            }
+            match read_upload(&path, budget) {
+                Ok((file, size)) => {
+                    budget = budget.saturating_sub(size);
+                    files.push(file);
+                }
+                Err(reason) => rejected.push(format!("{} ({})", file_name(&path), reason)),
+            }
+        }
+
+        if !rejected.is_empty() {
+            let message = format!("Skipped {}", rejected.join(", "));
+            if let Err(e) = app.emit("toast", serde_json::json!({ "message": message })) {
+                error!("[view:upload] {}", e);
+            }
+        }
+        if files.is_empty() {
+            return;
+        }
+
+        let result = app
+            .get_webview("main")
+            .ok_or_else(|| "The main webview does not exist".to_string())
+            .and_then(|main| {
+                let files = serde_json::to_string(&files).map_err(|e| e.to_string())?;
+                main.eval(&attach_script(&files)).map_err(|e| e.to_string())
+            });
+        if let Err(e) = result {
+            error!("[view:upload] Failed to attach files: {}", e);
+        }
+    });
+}
+
+/// Hands `files` to `ChatAsk.attach`, the titlebar shows a `toast` when the page
+/// has nowhere to attach them.
+fn attach_script(files: &str) -> String {
+    format!(
+        "if (!window.ChatAsk?.attach({})) window.__TAURI__.event.emitTo('titlebar', 'toast', {{ \
+         message: 'Could not attach the files, the page has no upload button' }});",
+        files
+    )
+}
+
+/// A dropped file as handed to `ChatAsk.attach`, with the content base64 encoded,
+/// and its size. Files over the `budget` left for the drop are refused.
+fn read_upload(path: &Path, budget: u64) -> Result<(serde_json::Value, u64), String> {
+    let metadata = fs::metadata(path).map_err(|e| e.to_string())?;
+    if !metadata.is_file() {
+        return Err("not a file".into());
+    }
+    if metadata.len() > UPLOAD_MAX_SIZE {
+        return Err(format!("over {} MB", UPLOAD_MAX_SIZE / 1024 / 1024));
+    }
+    if metadata.len() > budget {
+        return Err(format!(
+            "over {} MB in total",
+            UPLOAD_MAX_SIZE / 1024 / 1024
+        ));
+    }
+    let extension = path
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    if UPLOAD_BLOCKED_EXTENSIONS.contains(&extension.as_str()) {
+        return Err("unsupported type".into());
+    }
+
+    let data = fs::read(path).map_err(|e| e.to_string())?;
+    let size = data.len() as u64;
+    let file = serde_json::json!({
+        "name": file_name(path),
+        "type": mime_type(&extension),
+        "data": STANDARD.encode(data),
+    });
+    Ok((file, size))
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+/// MIME types of the formats ChatGPT handles specially, the page sniffs the rest.
+fn mime_type(extension: &str) -> &'static str {
+    match extension {
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "pdf" => "application/pdf",
+        "txt" => "text/plain",
+        "md" => "text/markdown",
+        "csv" => "text/csv",
+        "json" => "application/json",
+        _ => "",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read_upload_refuses_files_over_the_budget() {
+        let path = std::env::temp_dir().join(format!("chatgpt-upload-{}.txt", std::process::id()));
+        fs::write(&path, "hello").unwrap();
+
+        let within = read_upload(&path, 5);
+        let over = read_upload(&path, 4);
+        let _ = fs::remove_file(&path);
+
+        let (file, size) = within.unwrap();
+        assert_eq!(size, 5);
+        assert_eq!(file["data"], STANDARD.encode("hello"));
+        assert_eq!(file["type"], "text/plain");
+        assert_eq!(over.unwrap_err(), "over 50 MB in total");
+    }
+}
@@ -0,0 +1,54 @@
+use log::{error, info};
+use notify::{Event, EventKind, RecursiveMode, Watcher};
+use std::{fs, path::Path, sync::Mutex};
+use tauri::{AppHandle, Manager};
+
+use crate::core::conf::AppConf;
+
+/// Watches the scripts folder and re-applies changed scripts and `custom.css`
+/// to the live main view, since initialization scripts only run when a page loads.
+pub fn init(app: &AppHandle) -> Result<(), Box<dyn std::error::Error>> {
+    let scripts_dir = AppConf::get_scripts_path(app)?;
+
+    let handle = app.clone();
+    let mut watcher = notify::recommended_watcher(move |res: notify::Result<Event>| match res {
+        Ok(event) => {
+            if matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_)) {
+                for path in event.paths {
+                    reapply(&handle, &path);
+                }
+            }
+        }
+        Err(e) => error!("[scripts:watch] {}", e),
+    })?;
+    watcher.watch(&scripts_dir, RecursiveMode::NonRecursive)?;
+
+    // Managed state keeps the watcher alive for the lifetime of the app
+    app.manage(Mutex::new(watcher));
+    info!("[scripts:watch] Watching {}", scripts_dir.display());
+    Ok(())
+}
+
+fn reapply(app: &AppHandle, path: &Path) {
+    let Some(filename) = path.file_name().and_then(|name| name.to_str()) else {
+        return;
+    };
+
+    let script = match path.extension().and_then(|ext| ext.to_str()) {
+        Some("js") => fs::read_to_string(path).ok(),
+        _ if filename == "custom.css" => AppConf::load_style(app, filename),
+        _ => return,
+    };
+    let Some(script) = script else {
+        error!("[scripts:watch] Failed to read {}", path.display());
+        return;
+    };
+
+    let Some(main) = app.get_webview("main") else {
+        return;
+    };
+    match main.eval(&script) {
+        Ok(_) => info!("[scripts:watch] Re-applied {}", filename),
+        Err(e) => error!("[scripts:watch] Failed to apply {}: {}", filename, e),
+    }
+}
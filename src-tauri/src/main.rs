@@ -2,27 +2,109 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 mod core;
-use core::{cmd, setup, window};
+use core::{
+    cmd, download, find, history, idle, menu, model, navigation, notification, setup, shortcuts,
+    updater, window,
+};
 
 fn main() {
     tauri::Builder::default()
         .plugin(tauri_plugin_os::init())
         .plugin(tauri_plugin_shell::init())
         .plugin(tauri_plugin_dialog::init())
+        .plugin(tauri_plugin_global_shortcut::Builder::new().build())
+        .plugin(tauri_plugin_clipboard_manager::init())
+        .manage(find::FindState::default())
+        .manage(history::ConversationHistory::default())
+        .manage(download::LastDownload::default())
+        .manage(download::DownloadedFiles::default())
+        .manage(navigation::MainHistory::default())
+        .manage(model::ModelReplies::default())
+        .menu(menu::init)
         .invoke_handler(tauri::generate_handler![
             cmd::view_reload,
+            cmd::reload_main,
+            cmd::new_chat,
+            cmd::get_main_url,
+            cmd::export_conversation_pdf,
             cmd::view_url,
-            cmd::view_go_forward,
-            cmd::view_go_back,
-            cmd::set_view_ask,
+            navigation::go_back,
+            navigation::go_forward,
+            navigation::can_go_back,
+            navigation::can_go_forward,
+            cmd::set_ask_mode,
+            cmd::set_reference_url,
+            cmd::set_split_view,
+            cmd::set_split_ratio,
+            cmd::toggle_titlebar,
             cmd::get_app_conf,
+            cmd::export_settings,
+            cmd::import_settings,
+            cmd::open_scripts_folder,
+            cmd::list_script_presets,
+            cmd::apply_script_preset,
             cmd::window_pin,
+            cmd::toggle_always_on_top,
+            cmd::resize_window,
+            cmd::resize_preset,
+            cmd::snap_left,
+            cmd::snap_right,
+            cmd::snap_full,
+            cmd::snap_center,
+            cmd::get_display_info,
+            cmd::quit,
+            cmd::confirm_quit_response,
+            cmd::set_window_title,
+            cmd::toggle_visible_on_all_workspaces,
             cmd::ask_sync,
             cmd::ask_send,
+            cmd::submit_ask,
+            cmd::clear_ask,
+            cmd::new_window,
+            cmd::relaunch,
+            cmd::get_config,
+            cmd::update_config,
+            cmd::focus_composer,
+            cmd::stop_generating,
+            cmd::toggle_sidebar,
+            cmd::sidebar_toggled,
+            cmd::toggle_mute,
+            cmd::screenshot,
+            cmd::copy_last_response,
+            cmd::copy_response_text,
+            cmd::paste_plain,
             cmd::set_theme,
+            cmd::set_opacity,
+            cmd::switch_profile,
+            cmd::toggle_incognito,
+            cmd::toggle_devtools,
+            cmd::clear_site_data,
+            cmd::reset_onboarding,
+            cmd::zoom_in,
+            cmd::zoom_out,
+            cmd::zoom_reset,
+            download::reveal_download,
+            find::find_start,
+            find::find_next,
+            find::find_prev,
+            find::find_close,
+            history::next_conversation,
+            history::prev_conversation,
+            idle::idle_activity,
+            model::get_current_model,
+            model::set_model,
+            model::model_reply,
+            notification::response_complete,
             window::open_settings,
+            shortcuts::set_shortcut,
+            shortcuts::reset_shortcuts,
+            shortcuts::get_shortcuts,
+            updater::check_for_updates,
+            updater::install_update,
+            updater::confirm_restart_response,
         ])
         .setup(setup::init)
-        .run(tauri::generate_context!())
-        .expect("error while running lencx/ChatGPT application");
+        .build(tauri::generate_context!())
+        .expect("error while running lencx/ChatGPT application")
+        .run(setup::handle_run_event);
 }
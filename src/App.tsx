@@ -1,5 +1,8 @@
+import { useEffect } from 'react';
 import { getCurrentWebview } from '@tauri-apps/api/webview';
 
+import useTheme from '~hooks/useTheme';
+
 import Titlebar from '~view/Titlebar';
 import Ask from '~view/Ask';
 import Settings from '~view/Settings';
@@ -12,5 +15,13 @@ const viewMap = {
 
 export default function App() {
   const webview = getCurrentWebview();
-  return viewMap[webview.label as keyof typeof viewMap] || null;
+  const theme = useTheme();
+
+  useEffect(() => {
+    document.documentElement.classList.toggle('dark', theme === 'dark');
+  }, [theme]);
+
+  // Views of the windows opened by `new_window` carry the window label, e.g. `core-2-ask`
+  const view = webview.label.replace(/^core-\d+-/, '');
+  return viewMap[view as keyof typeof viewMap] || null;
 }
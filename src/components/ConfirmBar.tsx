@@ -0,0 +1,37 @@
+interface ConfirmBarProps {
+  message: string;
+  confirmLabel: string;
+  onConfirm: () => void;
+  onCancel: () => void;
+}
+
+export default function ConfirmBar({ message, confirmLabel, onConfirm, onCancel }: ConfirmBarProps) {
+  const handleKeyDown = (e: React.KeyboardEvent<HTMLDivElement>) => {
+    if (e.key === 'Escape') {
+      e.preventDefault();
+      onCancel();
+    }
+  };
+
+  return (
+    <div
+      onKeyDown={handleKeyDown}
+      className="absolute inset-y-0 right-2 flex items-center gap-2 text-[11px] text-slate-700 dark:text-slate-200"
+    >
+      <span>{message}</span>
+      <button
+        autoFocus
+        onClick={onConfirm}
+        className="h-[18px] px-2 rounded-sm bg-red-500/80 text-white"
+      >
+        {confirmLabel}
+      </button>
+      <button
+        onClick={onCancel}
+        className="h-[18px] px-2 rounded-sm bg-slate-200 dark:bg-slate-100/10"
+      >
+        Cancel
+      </button>
+    </div>
+  );
+}
@@ -0,0 +1,45 @@
+import { useEffect, useRef } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import debounce from 'lodash/debounce';
+
+interface FindBarProps {
+  onClose: () => void;
+}
+
+export default function FindBar({ onClose }: FindBarProps) {
+  const inputRef = useRef<HTMLInputElement>(null);
+
+  useEffect(() => {
+    inputRef.current?.focus();
+  }, []);
+
+  const handleInput = debounce((query: string) => {
+    invoke('find_start', { query });
+  }, 200);
+
+  const handleKeyDown = (e: React.KeyboardEvent<HTMLInputElement>) => {
+    if (e.key === 'Enter') {
+      e.preventDefault();
+      invoke(e.shiftKey ? 'find_prev' : 'find_next');
+    } else if (e.key === 'Escape') {
+      e.preventDefault();
+      handleInput.cancel();
+      invoke('find_close');
+      onClose();
+    }
+  };
+
+  return (
+    <div className="absolute inset-y-0 right-2 flex items-center">
+      <input
+        ref={inputRef}
+        onChange={(e) => handleInput(e.target.value)}
+        onKeyDown={handleKeyDown}
+        onBlur={() => handleInput.flush()}
+        spellCheck="false"
+        className="h-[18px] w-[180px] px-2 rounded-sm text-[11px] outline-none bg-slate-200 dark:bg-slate-100/10 text-slate-700 dark:text-slate-200"
+        placeholder="Find in page (⏎ next, ⇧⏎ previous)"
+      />
+    </div>
+  );
+}
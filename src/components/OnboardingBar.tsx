@@ -0,0 +1,31 @@
+interface OnboardingBarProps {
+  onClose: () => void;
+}
+
+export default function OnboardingBar({ onClose }: OnboardingBarProps) {
+  const handleKeyDown = (e: React.KeyboardEvent<HTMLDivElement>) => {
+    if (e.key === 'Escape') {
+      e.preventDefault();
+      onClose();
+    }
+  };
+
+  return (
+    <div
+      onKeyDown={handleKeyDown}
+      className="absolute inset-0 flex items-center justify-center gap-2 bg-white dark:bg-app-gray-2 text-[11px] text-slate-700 dark:text-slate-200"
+    >
+      <span>
+        Use the arrows to go back and forward, the ask button to type in a separate
+        box below the chat, and the gear for settings.
+      </span>
+      <button
+        autoFocus
+        onClick={onClose}
+        className="h-[18px] px-2 rounded-sm bg-app-active text-white"
+      >
+        Got it
+      </button>
+    </div>
+  );
+}
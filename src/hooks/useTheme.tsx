@@ -1,21 +1,27 @@
 import { useState, useEffect } from 'react';
 import { getCurrentWindow } from '@tauri-apps/api/window';
+import { listen } from '@tauri-apps/api/event';
 
 export default function useTheme() {
   const [theme, setTheme] = useState<string | null>('light'); // ['light', 'dark']
 
   useEffect(() => {
     let unlisten: Function;
+    let themeChanged: Function;
     (async () => {
       let win = getCurrentWindow();
       setTheme(await win.theme() || '');
       unlisten = await win.onThemeChanged(({ payload: newTheme }) => {
         setTheme(newTheme);
       });
+      themeChanged = await listen<I.ThemeChanged>('theme-changed', ({ payload }) => {
+        setTheme(payload.resolved);
+      });
     })()
 
     return () => {
       unlisten?.();
+      themeChanged?.();
     };
   }, [])
 
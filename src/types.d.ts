@@ -1,9 +1,115 @@
 declare namespace I {
   export type AppConf = {
+    version: number;
     theme: 'light' | 'dark' | 'system';
     stay_on_top: boolean;
+    visible_on_all_workspaces: boolean;
     ask_mode: boolean;
     mac_titlebar_hidden: boolean;
+    window_width: number;
+    window_height: number;
+    window_x: number | null;
+    window_y: number | null;
+    window_presets: Record<string, { width: number; height: number }>;
+    min_width: number;
+    min_height: number;
+    start_url: string;
+    custom_titlebar: boolean;
+    titlebar_height: number;
+    titlebar_position: 'top' | 'bottom';
+    manual_layout_only: boolean;
+    split_view: boolean;
+    reference_url: string | null;
+    split_ratio: number;
+    data_dir: string | null;
+    download_dir: string | null;
+    open_downloads_on_finish: boolean;
+    download_actions: Record<string, 'open' | 'reveal' | 'none'>;
+    max_downloads: number;
+    enable_tray: boolean;
+    close_to_tray: boolean;
+    close_to_tray_platforms: Record<string, boolean>;
+    confirm_quit: boolean;
+    shortcuts: Record<string, string>;
+    focus_composer_on_show: boolean;
+    ask_height: number;
+    transparent_ask: boolean;
+    host_zoom: Record<string, number>;
+    opacity: number;
+    rounded_corners: boolean;
+    background_color: string | null;
+    vibrancy: string | null;
+    profile: string;
+    incognito: boolean;
+    proxy_url: string | null;
+    user_agent: string;
+    restore_last_url: boolean;
+    auto_update: boolean;
+    update_channel: 'stable' | 'beta';
+    first_run: boolean;
+    last_url: string | null;
+    retry_interval_secs: number;
+    idle_reload_minutes: number;
+    notify_on_complete: boolean;
+    conversation_history_size: number;
+    title_template: string;
+    new_chat_path: string;
+    default_model: string | null;
+    allowed_hosts: string[];
+    auth_domains: string[];
+    spellcheck: boolean;
+    sidebar_collapsed: boolean;
+    muted: boolean;
+    reduce_motion: 'off' | 'on' | 'auto';
+    script_preset: string | null;
+    watch_scripts: boolean;
+    auto_recover_webview: boolean;
+    log_level: 'error' | 'warn' | 'info' | 'debug' | 'trace';
+    enable_devtools: boolean;
+  }
+
+  export type ThemeChanged = {
+    theme: AppConf['theme'];
+    resolved: 'light' | 'dark';
+  }
+
+  export type Toast = {
+    message: string;
+  }
+
+  export type DisplayInfo = {
+    scale_factor: number;
+    monitor_width: number;
+    monitor_height: number;
+    work_area: { x: number; y: number; width: number; height: number };
+  }
+
+  export type UpdateInfo = {
+    version: string;
+    notes: string | null;
+  }
+
+  export type DownloadProgress = {
+    received: number;
+    total: number | null;
+  }
+
+  export type MainLoad = {
+    url: string;
+  }
+
+  export type WebviewCrashed = {
+    reason: string;
+    recovering: boolean;
+  }
+
+  export type MainUrlChanged = {
+    url: string;
+  }
+
+  export type TitleChange = {
+    url: string;
+    title: string;
   }
 
   export interface SVG extends React.SVGProps<SVGSVGElement> {
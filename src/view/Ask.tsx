@@ -1,5 +1,6 @@
 import { useState, useEffect, useRef } from 'react';
 import { invoke } from '@tauri-apps/api/core';
+import { getCurrentWebview } from '@tauri-apps/api/webview';
 import { useHotkeys } from 'react-hotkeys-hook';
 import useInfo from '~hooks/useInfo';
 import SendIcon from '~icons/Send';
@@ -8,8 +9,16 @@ import debounce from 'lodash/debounce';
 export default function ChatInput() {
   const inputRef = useRef<HTMLTextAreaElement>(null);
   const [message, setMessage] = useState('');
+  const [floating, setFloating] = useState(false);
   const { isMac } = useInfo();
 
+  useEffect(() => {
+    // Transparent webviews are unsupported on macOS, the strip stays opaque there
+    invoke<I.AppConf>('get_app_conf').then((conf) => {
+      setFloating(conf.transparent_ask && !isMac);
+    });
+  }, [isMac]);
+
   useEffect(() => {
     const syncMessage = debounce(async () => {
       try {
@@ -23,6 +32,25 @@ export default function ChatInput() {
     return () => syncMessage.cancel(); // Cleanup debounce on unmount
   }, [message]);
 
+  useEffect(() => {
+    let askClear: Function;
+
+    (async () => {
+      // Sent by `clear_ask`, also after every successful `submit_ask`
+      askClear = await getCurrentWebview().listen('ask:clear', () => {
+        setMessage('');
+        if (inputRef.current) {
+          inputRef.current.value = '';
+          inputRef.current.focus();
+        }
+      });
+    })();
+
+    return () => {
+      askClear && askClear();
+    };
+  }, []);
+
   useHotkeys(isMac ? 'meta+enter' : 'ctrl+enter', async (event: KeyboardEvent) => {
     event.preventDefault();
     await handleSend();
@@ -37,19 +65,14 @@ export default function ChatInput() {
   const handleSend = async () => {
     if (!message) return;
     try {
-      await invoke('ask_send', { message: JSON.stringify(message) });
+      await invoke('submit_ask', { prompt: message });
     } catch (error) {
       console.error('Error sending message:', error);
     }
-    setMessage('');
-    if (inputRef.current) {
-      inputRef.current.value = '';
-      inputRef.current.focus();
-    }
   };
 
   return (
-    <div className="relative flex h-full dark:bg-app-gray-2/[0.98] bg-gray-100 dark:text-slate-200 items-center gap-1">
+    <div className={`relative flex h-full ${floating ? 'dark:bg-app-gray-2/80 bg-gray-100/80' : 'dark:bg-app-gray-2/[0.98] bg-gray-100'} dark:text-slate-200 items-center gap-1`}>
       <textarea
         ref={inputRef}
         onChange={handleInput}
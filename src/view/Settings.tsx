@@ -1,5 +1,41 @@
+import { useState, useEffect } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+
+import useInfo from '~hooks/useInfo';
+
 export default function Settings() {
+  const [conf, setConf] = useState<I.AppConf | null>(null);
+  const { isMac } = useInfo();
+
+  useEffect(() => {
+    invoke<I.AppConf>('get_config').then(setConf);
+  }, []);
+
+  const update = async (patch: Partial<I.AppConf>) => {
+    try {
+      setConf(await invoke<I.AppConf>('update_config', { json: patch }));
+    } catch (error) {
+      console.error('Error updating config:', error);
+    }
+  };
+
+  if (!conf) return null;
+
   return (
-    <div>Settings</div>
+    <div className="p-4 dark:bg-app-gray-2 dark:text-slate-200 h-full text-sm">
+      <label className="flex items-center gap-2">
+        <input
+          type="checkbox"
+          checked={conf.notify_on_complete}
+          onChange={(e) => update({ notify_on_complete: e.target.checked })}
+        />
+        Notify when a response completes in the background
+      </label>
+      <p className="mt-1 ml-6 text-gray-500">
+        {isMac
+          ? 'Clicking the notification brings ChatGPT to the front.'
+          : 'Clicking the notification only focuses the window on macOS, use the tray icon here.'}
+      </p>
+    </div>
   )
-}
\ No newline at end of file
+}
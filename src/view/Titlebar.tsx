@@ -1,14 +1,21 @@
 import { useEffect, useState, useMemo } from 'react';
 import { invoke } from '@tauri-apps/api/core';
 import { getCurrentWindow } from '@tauri-apps/api/window';
+import { getCurrentWebview } from '@tauri-apps/api/webview';
+import { emit } from '@tauri-apps/api/event';
 import { open } from '@tauri-apps/plugin-shell';
 import { debounce } from 'lodash';
 import clsx from 'clsx';
 
 import useInfo from '~hooks/useInfo';
+import FindBar from '~components/FindBar';
+import ConfirmBar from '~components/ConfirmBar';
+import OnboardingBar from '~components/OnboardingBar';
 import ReloadIcon from '~icons/Reload';
 import PinIcon from '~icons/Pin';
 import UnPinIcon from '~icons/UnPin';
+import VolumeIcon from '~icons/Volume';
+import VolumeOffIcon from '~icons/VolumeOff';
 import LinkIcon from '~icons/Link';
 import AskIcon from '~icons/Ask';
 import SettingIcon from '~icons/Setting';
@@ -25,21 +32,59 @@ export default function Titlebar() {
   const [enableAsk, setEnableAsk] = useState(false);
   const [fullScreen, setFullScreen] = useState(false);
   const [isPin, setPin] = useState(false);
+  const [muted, setMuted] = useState(false);
   const [isTitlebarHidden, setTitlebarHidden] = useState(false);
+  const [download, setDownload] = useState<I.DownloadProgress | null>(null);
+  const [toast, setToast] = useState<string | null>(null);
+  const [findOpen, setFindOpen] = useState(false);
+  const [confirmClear, setConfirmClear] = useState(false);
+  const [confirmQuit, setConfirmQuit] = useState(false);
+  const [update, setUpdate] = useState<I.UpdateInfo | null>(null);
+  const [updateReady, setUpdateReady] = useState(false);
+  // Off for builds without the `updater` feature or a pubkey
+  const [hasUpdater, setHasUpdater] = useState(true);
+  const [onboarding, setOnboarding] = useState(false);
+  const [loading, setLoading] = useState(true);
+  const [vibrancy, setVibrancy] = useState(false);
+  const [canGoBack, setCanGoBack] = useState(false);
+  const [canGoForward, setCanGoForward] = useState(false);
 
   const titlebarHidden = info.isMac && isTitlebarHidden;
 
   useEffect(() => {
     const win = getCurrentWindow();
+    // Scoped to this webview, so events meant for another window's titlebar are
+    // left out. Global emits still arrive.
+    const webview = getCurrentWebview();
     let winResize: Function;
     let changeUrl: Function;
+    let changeTitle: Function;
+    let mainUrlChanged: Function;
+    let downloadProgress: Function;
+    let downloadFinished: Function;
+    let themeChanged: Function;
+    let alwaysOnTopChanged: Function;
+    let muteChanged: Function;
+    let toastShown: Function;
+    let findOpened: Function;
+    let clearSiteDataConfirm: Function;
+    let quitConfirm: Function;
+    let firstRun: Function;
+    let updateAvailable: Function;
+    let updateDownloading: Function;
+    let updateReadyShown: Function;
+    let mainLoading: Function;
+    let mainLoaded: Function;
+    let toastTimer: ReturnType<typeof setTimeout>;
 
     invoke<I.AppConf>('get_app_conf')
       .then((v) => {
         setEnableAsk(v.ask_mode);
         setPin(v.stay_on_top);
+        setMuted(v.muted);
         setTheme(v.theme);
         setTitlebarHidden(v.mac_titlebar_hidden);
+        setVibrancy(!!v.vibrancy);
       });
 
     (async () => {
@@ -50,7 +95,7 @@ export default function Titlebar() {
         setFullScreen(full);
       }, 50))
 
-      changeUrl = await getCurrentWindow().listen('navigation:change', (event: any) => {
+      changeUrl = await webview.listen('navigation:change', (event: any) => {
         const { url } = event.payload;
         setUrl(url);
 
@@ -61,11 +106,107 @@ export default function Titlebar() {
           setHostname(url);
         }
       })
+
+      mainUrlChanged = await webview.listen<I.MainUrlChanged>('main-url-changed', async () => {
+        setCanGoBack(await invoke<boolean>('can_go_back'));
+        setCanGoForward(await invoke<boolean>('can_go_forward'));
+      })
+
+      changeTitle = await webview.listen<I.TitleChange>('title:change', (event) => {
+        const { url, title } = event.payload;
+        // Only conversations have a name, new chats reset to the default title
+        const isChat = /\/c\/[^/]+/.test(new URL(url).pathname);
+        invoke('set_window_title', { title: isChat ? title : '' });
+      })
+
+      downloadProgress = await webview.listen<I.DownloadProgress>('download-progress', (event) => {
+        setDownload(event.payload);
+      })
+
+      downloadFinished = await webview.listen('download-finished', () => {
+        setDownload(null);
+      })
+
+      themeChanged = await webview.listen<I.ThemeChanged>('theme-changed', (event) => {
+        setTheme(event.payload.theme);
+      })
+
+      alwaysOnTopChanged = await webview.listen<boolean>('always-on-top-changed', (event) => {
+        setPin(event.payload);
+      })
+
+      muteChanged = await webview.listen<boolean>('mute-changed', (event) => {
+        setMuted(event.payload);
+      })
+
+      findOpened = await webview.listen('find-open', () => {
+        setFindOpen(true);
+      })
+
+      clearSiteDataConfirm = await webview.listen('clear-site-data-confirm', () => {
+        setConfirmClear(true);
+      })
+
+      quitConfirm = await webview.listen('confirm-quit', () => {
+        setConfirmQuit(true);
+      })
+
+      mainLoading = await webview.listen<I.MainLoad>('main-loading', () => {
+        setLoading(true);
+      })
+
+      mainLoaded = await webview.listen<I.MainLoad>('main-loaded', () => {
+        setLoading(false);
+      })
+
+      toastShown = await webview.listen<I.Toast>('toast', (event) => {
+        setToast(event.payload.message);
+        clearTimeout(toastTimer);
+        toastTimer = setTimeout(() => setToast(null), 2000);
+      })
+
+      firstRun = await webview.listen('first-run', () => {
+        setOnboarding(true);
+      })
+
+      updateAvailable = await webview.listen<I.UpdateInfo>('update-available', (event) => {
+        setUpdate(event.payload);
+      })
+
+      updateDownloading = await webview.listen<I.DownloadProgress>('update-downloading', (event) => {
+        setDownload(event.payload);
+      })
+
+      updateReadyShown = await webview.listen<I.UpdateInfo>('update-ready', () => {
+        setDownload(null);
+        setUpdateReady(true);
+      })
+
+      // Sent last, so nothing emitted in response is missed
+      emit('titlebar:ready');
     })();
 
     return () => {
       winResize && winResize();
       changeUrl && changeUrl();
+      changeTitle && changeTitle();
+      mainUrlChanged && mainUrlChanged();
+      downloadProgress && downloadProgress();
+      downloadFinished && downloadFinished();
+      themeChanged && themeChanged();
+      alwaysOnTopChanged && alwaysOnTopChanged();
+      muteChanged && muteChanged();
+      toastShown && toastShown();
+      findOpened && findOpened();
+      clearSiteDataConfirm && clearSiteDataConfirm();
+      quitConfirm && quitConfirm();
+      firstRun && firstRun();
+      updateAvailable && updateAvailable();
+      updateDownloading && updateDownloading();
+      updateReadyShown && updateReadyShown();
+      mainLoading && mainLoading();
+      mainLoaded && mainLoaded();
+      clearTimeout(toastTimer);
     }
   }, [])
 
@@ -74,11 +215,11 @@ export default function Titlebar() {
   };
 
   const handleGoForward = () => {
-    invoke('view_go_forward');
+    invoke('go_forward');
   };
 
   const handleGoBack = () => {
-    invoke('view_go_back');
+    invoke('go_back');
   };
 
   const handlePin = (isPin: boolean) => {
@@ -86,9 +227,13 @@ export default function Titlebar() {
     invoke('window_pin', { pin: isPin });
   };
 
+  const handleMute = () => {
+    invoke('toggle_mute');
+  };
+
   const handleAsk = () => {
     setEnableAsk(!enableAsk);
-    invoke('set_view_ask', { enabled: !enableAsk });
+    invoke('set_ask_mode', { enabled: !enableAsk });
   };
 
   const handleTheme = (theme: string) => {
@@ -112,6 +257,36 @@ export default function Titlebar() {
     open(url);
   };
 
+  const handleClearSiteData = () => {
+    setConfirmClear(false);
+    invoke('clear_site_data', { confirmed: true });
+  };
+
+  const handleQuit = (confirmed: boolean) => {
+    setConfirmQuit(false);
+    invoke('confirm_quit_response', { confirmed });
+  };
+
+  const handleInstallUpdate = async () => {
+    setUpdate(null);
+    try {
+      await invoke('install_update');
+    } catch (error) {
+      setDownload(null);
+      if (String(error).includes('This build has no updater')) {
+        setHasUpdater(false);
+        return;
+      }
+      emit('toast', { message: 'Failed to install the update' });
+      console.error('Error installing update:', error);
+    }
+  };
+
+  const handleRestart = (confirmed: boolean) => {
+    setUpdateReady(false);
+    invoke('confirm_restart_response', { confirmed });
+  };
+
   const handleSetting = () => {
     invoke('open_settings');
   };
@@ -126,13 +301,30 @@ export default function Titlebar() {
         {isPin
           ? <PinIcon action onClick={() => handlePin(false)} />
           : <UnPinIcon action onClick={() => handlePin(true)} />}
+        {muted
+          ? <VolumeOffIcon title="Unmute" action onClick={handleMute} />
+          : <VolumeIcon title="Mute" action onClick={handleMute} />}
         <SettingIcon action onClick={handleSetting} />
       </div>
     )
-  }, [titlebarHidden, themeIcon, isPin])
+  }, [titlebarHidden, themeIcon, isPin, muted])
+
+  const renderDownload = useMemo(() => {
+    if (!download) return null;
+    const { received, total } = download;
+    return (
+      <div className="absolute bottom-0 left-0 w-full h-[2px] overflow-hidden">
+        {total
+          ? <div className="h-full bg-app-active" style={{ width: `${Math.min(received / total, 1) * 100}%` }} />
+          : <div className="h-full w-full bg-app-active animate-pulse" />}
+      </div>
+    )
+  }, [download])
 
   return (
-    <div data-tauri-drag-region className={clsx('flex group pr-2 h-full cursor-default select-none dark:bg-app-gray-2 justify-between', {
+    <div data-tauri-drag-region className={clsx('relative flex group pr-2 h-full cursor-default select-none justify-between', {
+      // Leave the window's material visible behind the titlebar
+      'dark:bg-app-gray-2': !(vibrancy && info.isMac),
       'pl-[80px]': !fullScreen && info.isMac,
       'pl-[10px]': fullScreen || !info.isMac,
     })}>
@@ -151,15 +343,22 @@ export default function Titlebar() {
           </span>
         )}
         <ArrowLeftIcon
-          action
+          action={canGoBack}
           onClick={handleGoBack}
+          className={clsx({ 'opacity-40': !canGoBack })}
         />
         <ArrowLeftIcon
-          action
+          action={canGoForward}
           onClick={handleGoForward}
-          className="rotate-180"
+          className={clsx('rotate-180', { 'opacity-40': !canGoForward })}
+        />
+        <ReloadIcon
+          action
+          onClick={handleRefresh}
+          className={clsx({
+            'animate-spin': loading,
+          })}
         />
-        <ReloadIcon action onClick={handleRefresh} />
         <AskIcon
           action
           onClick={handleAsk}
@@ -172,7 +371,47 @@ export default function Titlebar() {
         'hidden group-hover:flex': titlebarHidden,
         'flex': !titlebarHidden,
       })} />
+      {toast && (
+        <span className="absolute left-1/2 top-1/2 -translate-x-1/2 -translate-y-1/2 text-[10px] text-slate-700 dark:text-gray-400">
+          {toast}
+        </span>
+      )}
       {renderSettings}
+      {renderDownload}
+      {onboarding && <OnboardingBar onClose={() => setOnboarding(false)} />}
+      {findOpen && <FindBar onClose={() => setFindOpen(false)} />}
+      {confirmClear && (
+        <ConfirmBar
+          message="Clear cookies and cache? You will be signed out."
+          confirmLabel="Clear"
+          onConfirm={handleClearSiteData}
+          onCancel={() => setConfirmClear(false)}
+        />
+      )}
+      {confirmQuit && (
+        <ConfirmBar
+          message="Quit ChatGPT? Anything typed but not sent is lost."
+          confirmLabel="Quit"
+          onConfirm={() => handleQuit(true)}
+          onCancel={() => handleQuit(false)}
+        />
+      )}
+      {hasUpdater && update && (
+        <ConfirmBar
+          message={`ChatGPT ${update.version} is available.`}
+          confirmLabel="Update"
+          onConfirm={handleInstallUpdate}
+          onCancel={() => setUpdate(null)}
+        />
+      )}
+      {hasUpdater && updateReady && (
+        <ConfirmBar
+          message="Update installed. Restart ChatGPT now?"
+          confirmLabel="Restart"
+          onConfirm={() => handleRestart(true)}
+          onCancel={() => handleRestart(false)}
+        />
+      )}
     </div>
   );
 }
\ No newline at end of file
@@ -1,5 +1,7 @@
 /** @type {import('tailwindcss').Config} */
 export default {
+  // Toggled from `useTheme` so the theme can change without a restart
+  darkMode: 'selector',
   content: [
     './index.html',
     './src/**/*.{js,jsx,ts,tsx}',